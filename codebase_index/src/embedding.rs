@@ -0,0 +1,389 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use governor::clock::DefaultClock;
+use governor::state::direct::NotKeyed;
+use governor::state::InMemoryState;
+use governor::{Quota, RateLimiter};
+use serde_json::{json, Value};
+use std::env;
+use std::num::NonZeroU32;
+use std::time::Duration;
+
+/// Decouples the embedding model from the ingestion pipeline so alternative
+/// providers (local, self-hosted) can be swapped in without touching
+/// `ingestion`/`updater`.
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    /// Embeds a batch of texts, preserving input order in the result.
+    async fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f64>>>;
+
+    /// Dimensionality of the vectors this provider returns.
+    fn dimensions(&self) -> usize;
+
+    /// Maximum number of texts this provider accepts in a single `embed` call.
+    fn max_batch(&self) -> usize;
+
+    /// Identifier of the underlying model, surfaced in logs so a run's output
+    /// can be traced back to the provider that produced it.
+    fn model_id(&self) -> &str;
+}
+
+/// Builds the `EmbeddingProvider` named by `name` ("gemini", "openai",
+/// "ollama", "self-hosted", or "local"), reading that provider's own env vars for
+/// credentials/endpoints/model selection.
+pub fn provider_from_name(name: &str) -> Result<Box<dyn EmbeddingProvider>> {
+    match name {
+        "gemini" => Ok(Box::new(GeminiProvider::new()?)),
+        "openai" => Ok(Box::new(OpenAiProvider::new()?)),
+        "ollama" => Ok(Box::new(OllamaProvider::new()?)),
+        "self-hosted" | "self_hosted" => Ok(Box::new(SelfHostedProvider::new()?)),
+        "local" => Ok(Box::new(crate::local_embedding::LocalEmbeddingProvider::new()?)),
+        other => Err(anyhow::anyhow!(
+            "Unknown embedding provider '{}' (expected one of: gemini, openai, ollama, self-hosted, local)",
+            other
+        )),
+    }
+}
+
+/// Selects the `EmbeddingProvider` to use for this run. Defaults to
+/// `EMBEDDING_PROVIDER` env var (falling back to "gemini") so an offline or
+/// cost-free index can be built without touching command-line parsing.
+pub fn provider_from_env() -> Result<Box<dyn EmbeddingProvider>> {
+    let name = env::var("EMBEDDING_PROVIDER").unwrap_or_else(|_| "gemini".to_string());
+    provider_from_name(&name)
+}
+
+// gemini-embedding-001's native output size; only the full-size output is
+// L2-normalized by the API, so Matryoshka-truncated sizes must be renormalized.
+const GEMINI_NATIVE_DIMS: usize = 3072;
+
+/// The existing Gemini embedding path, extracted behind `EmbeddingProvider`.
+/// `embed` sends one `embedContent` request per text, in order; Gemini's
+/// `batchEmbedContents` endpoint is not used.
+pub struct GeminiProvider {
+    client: reqwest::Client,
+    limiter: RateLimiter<NotKeyed, InMemoryState, DefaultClock>,
+    api_key: String,
+    /// Matryoshka-truncated output size requested from the API (e.g. 768 or
+    /// 1536), for cheaper storage and faster vector search. `None` keeps the
+    /// native `GEMINI_NATIVE_DIMS`-size, already-normalized output.
+    output_dims: Option<usize>,
+}
+
+impl GeminiProvider {
+    pub fn new() -> Result<Self> {
+        let api_key = env::var("GEMINI_API_KEY")
+            .map_err(|_| anyhow::anyhow!("GEMINI_API_KEY environment variable not set"))?;
+        let output_dims = env::var("GEMINI_EMBEDDING_DIMENSIONS")
+            .ok()
+            .map(|dims| dims.parse::<usize>().map_err(|_| anyhow::anyhow!("GEMINI_EMBEDDING_DIMENSIONS must be a positive integer")))
+            .transpose()?
+            .filter(|&dims| dims != GEMINI_NATIVE_DIMS);
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .pool_max_idle_per_host(3000)
+            .pool_idle_timeout(Duration::from_secs(30))
+            .build()?;
+
+        Ok(Self {
+            client,
+            limiter: RateLimiter::direct(Quota::per_minute(NonZeroU32::new(4000).unwrap())),
+            api_key,
+            output_dims,
+        })
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for GeminiProvider {
+    async fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f64>>> {
+        let mut results = Vec::with_capacity(texts.len());
+
+        for text in texts {
+            if text.trim().is_empty() {
+                results.push(Vec::new());
+                continue;
+            }
+
+            self.limiter.until_ready().await;
+
+            let mut payload = json!({
+                "model": "models/gemini-embedding-001",
+                "content": { "parts": [{ "text": text }] },
+                "task_type": "SEMANTIC_SIMILARITY"
+            });
+            if let Some(dims) = self.output_dims {
+                payload["output_dimensionality"] = json!(dims);
+            }
+
+            let res = self.client
+                .post("https://generativelanguage.googleapis.com/v1beta/models/gemini-embedding-001:embedContent")
+                .header("x-goog-api-key", &self.api_key)
+                .header("Content-Type", "application/json")
+                .json(&payload)
+                .send()
+                .await?;
+
+            if !res.status().is_success() {
+                let status = res.status();
+                let error_text = res.text().await.unwrap_or_else(|_| "<could not read response body>".to_string());
+                return Err(anyhow::anyhow!("API returned error status {}: {}", status, error_text));
+            }
+
+            let body = res.json::<Value>().await?;
+            let values = body["embedding"]["values"].as_array()
+                .ok_or_else(|| anyhow::anyhow!("Invalid embedding response format, missing 'values' array: {:?}", body))?;
+
+            let mut vector = Vec::with_capacity(values.len());
+            for v in values {
+                vector.push(v.as_f64().ok_or_else(|| anyhow::anyhow!("Non-numeric value in embedding: {:?}", v))?);
+            }
+
+            // Gemini only L2-normalizes the full-size output; a truncated
+            // output comes back un-normalized and must be renormalized for
+            // cosine/dot-product search scores to be meaningful.
+            if self.output_dims.is_some() {
+                let norm = vector.iter().map(|v| v * v).sum::<f64>().sqrt();
+                if norm > 0.0 {
+                    for v in vector.iter_mut() {
+                        *v /= norm;
+                    }
+                }
+            }
+
+            results.push(vector);
+        }
+
+        Ok(results)
+    }
+
+    fn dimensions(&self) -> usize {
+        self.output_dims.unwrap_or(GEMINI_NATIVE_DIMS)
+    }
+
+    fn max_batch(&self) -> usize {
+        100
+    }
+
+    fn model_id(&self) -> &str {
+        "gemini-embedding-001"
+    }
+}
+
+/// Remote OpenAI-style embeddings endpoint (also used by OpenAI-compatible
+/// providers that mirror the `/v1/embeddings` request/response shape).
+pub struct OpenAiProvider {
+    client: reqwest::Client,
+    api_key: String,
+    base_url: String,
+    model: String,
+    dimensions: usize,
+}
+
+impl OpenAiProvider {
+    pub fn new() -> Result<Self> {
+        let api_key = env::var("OPENAI_API_KEY")
+            .map_err(|_| anyhow::anyhow!("OPENAI_API_KEY environment variable not set"))?;
+        let base_url = env::var("OPENAI_BASE_URL").unwrap_or_else(|_| "https://api.openai.com/v1".to_string());
+        let model = env::var("OPENAI_EMBEDDING_MODEL").unwrap_or_else(|_| "text-embedding-3-small".to_string());
+        let dimensions = match env::var("OPENAI_EMBEDDING_DIMENSIONS") {
+            Ok(dims) => dims.parse().map_err(|_| anyhow::anyhow!("OPENAI_EMBEDDING_DIMENSIONS must be a positive integer"))?,
+            Err(_) if model == "text-embedding-3-large" => 3072,
+            Err(_) => 1536,
+        };
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()?;
+
+        Ok(Self { client, api_key, base_url, model, dimensions })
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OpenAiProvider {
+    async fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f64>>> {
+        let res = self.client
+            .post(format!("{}/embeddings", self.base_url))
+            .bearer_auth(&self.api_key)
+            .json(&json!({ "model": self.model, "input": texts }))
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            let status = res.status();
+            let error_text = res.text().await.unwrap_or_else(|_| "<could not read response body>".to_string());
+            return Err(anyhow::anyhow!("OpenAI embeddings API returned error status {}: {}", status, error_text));
+        }
+
+        let body = res.json::<Value>().await?;
+        let data = body.get("data").and_then(|v| v.as_array())
+            .ok_or_else(|| anyhow::anyhow!("Invalid OpenAI embeddings response, missing 'data' array: {:?}", body))?;
+
+        data.iter()
+            .map(|entry| {
+                let values = entry.get("embedding").and_then(|v| v.as_array())
+                    .ok_or_else(|| anyhow::anyhow!("Invalid OpenAI embeddings entry, missing 'embedding' array: {:?}", entry))?;
+                values.iter()
+                    .map(|v| v.as_f64().ok_or_else(|| anyhow::anyhow!("Non-numeric value in embedding: {:?}", v)))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    fn max_batch(&self) -> usize {
+        2048
+    }
+
+    fn model_id(&self) -> &str {
+        &self.model
+    }
+}
+
+/// Local Ollama embeddings endpoint, for indexing without sending source
+/// text to any remote service.
+pub struct OllamaProvider {
+    client: reqwest::Client,
+    base_url: String,
+    model: String,
+    dimensions: usize,
+}
+
+impl OllamaProvider {
+    pub fn new() -> Result<Self> {
+        let base_url = env::var("OLLAMA_HOST").unwrap_or_else(|_| "http://localhost:11434".to_string());
+        let model = env::var("OLLAMA_EMBEDDING_MODEL").unwrap_or_else(|_| "nomic-embed-text".to_string());
+        let dimensions = env::var("OLLAMA_EMBEDDING_DIMENSIONS")
+            .ok()
+            .and_then(|dims| dims.parse().ok())
+            .unwrap_or(768);
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(60))
+            .build()?;
+
+        Ok(Self { client, base_url, model, dimensions })
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OllamaProvider {
+    async fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f64>>> {
+        let res = self.client
+            .post(format!("{}/api/embed", self.base_url))
+            .json(&json!({ "model": self.model, "input": texts }))
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            let status = res.status();
+            let error_text = res.text().await.unwrap_or_else(|_| "<could not read response body>".to_string());
+            return Err(anyhow::anyhow!(
+                "Ollama embeddings request returned error status {}: {} (is `ollama serve` running at {}?)",
+                status, error_text, self.base_url
+            ));
+        }
+
+        let body = res.json::<Value>().await?;
+        let embeddings = body.get("embeddings").and_then(|v| v.as_array())
+            .ok_or_else(|| anyhow::anyhow!("Invalid Ollama embeddings response, missing 'embeddings' array: {:?}", body))?;
+
+        embeddings.iter()
+            .map(|vector| {
+                vector.as_array()
+                    .ok_or_else(|| anyhow::anyhow!("Invalid Ollama embedding vector: {:?}", vector))?
+                    .iter()
+                    .map(|v| v.as_f64().ok_or_else(|| anyhow::anyhow!("Non-numeric value in embedding: {:?}", v)))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    fn max_batch(&self) -> usize {
+        100
+    }
+
+    fn model_id(&self) -> &str {
+        &self.model
+    }
+}
+
+/// A self-hosted embeddings endpoint speaking the same
+/// `{"model", "input"} -> {"embeddings"}` shape as `OllamaProvider`, for
+/// teams running their own model server instead of Ollama.
+pub struct SelfHostedProvider {
+    client: reqwest::Client,
+    url: String,
+    model: String,
+    dimensions: usize,
+}
+
+impl SelfHostedProvider {
+    pub fn new() -> Result<Self> {
+        let url = env::var("SELF_HOSTED_EMBEDDING_URL")
+            .map_err(|_| anyhow::anyhow!("SELF_HOSTED_EMBEDDING_URL environment variable not set"))?;
+        let model = env::var("SELF_HOSTED_EMBEDDING_MODEL").unwrap_or_else(|_| "self-hosted".to_string());
+        let dimensions: usize = env::var("SELF_HOSTED_EMBEDDING_DIMENSIONS")
+            .map_err(|_| anyhow::anyhow!("SELF_HOSTED_EMBEDDING_DIMENSIONS environment variable not set"))?
+            .parse()
+            .map_err(|_| anyhow::anyhow!("SELF_HOSTED_EMBEDDING_DIMENSIONS must be a positive integer"))?;
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(60))
+            .build()?;
+
+        Ok(Self { client, url, model, dimensions })
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for SelfHostedProvider {
+    async fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f64>>> {
+        let res = self.client
+            .post(&self.url)
+            .json(&json!({ "model": self.model, "input": texts }))
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            let status = res.status();
+            let error_text = res.text().await.unwrap_or_else(|_| "<could not read response body>".to_string());
+            return Err(anyhow::anyhow!("Self-hosted embeddings endpoint returned error status {}: {}", status, error_text));
+        }
+
+        let body = res.json::<Value>().await?;
+        let embeddings = body.get("embeddings").and_then(|v| v.as_array())
+            .ok_or_else(|| anyhow::anyhow!("Invalid self-hosted embeddings response, missing 'embeddings' array: {:?}", body))?;
+
+        embeddings.iter()
+            .map(|vector| {
+                vector.as_array()
+                    .ok_or_else(|| anyhow::anyhow!("Invalid self-hosted embedding vector: {:?}", vector))?
+                    .iter()
+                    .map(|v| v.as_f64().ok_or_else(|| anyhow::anyhow!("Non-numeric value in embedding: {:?}", v)))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    fn max_batch(&self) -> usize {
+        100
+    }
+
+    fn model_id(&self) -> &str {
+        &self.model
+    }
+}
@@ -0,0 +1,105 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Where a queued embedding job stands relative to the `embedSuperEntity`
+/// POST that finally commits it.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JobStatus {
+    Pending,
+    Failed,
+    Done,
+}
+
+/// Everything needed to redo an embedding job on resume: what to embed and
+/// where to post the result.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct QueuedJob {
+    pub entity_id: String,
+    pub chunk: String,
+    pub port: u16,
+    pub status: JobStatus,
+}
+
+/// Durable log of in-flight embedding jobs, keyed by chunk content hash, so
+/// a crash (or a deliberate Exit) while jobs are still pending doesn't lose
+/// un-posted embeddings, and embedding the same chunk twice across runs is
+/// a no-op. Mirrors `ingest_journal::JobState`'s flat map + atomic-write
+/// persistence, scoped to the embedding pipeline instead of the file walk.
+pub struct EmbeddingQueue {
+    path: PathBuf,
+    entries: Mutex<HashMap<u64, QueuedJob>>,
+}
+
+impl EmbeddingQueue {
+    fn queue_path() -> PathBuf {
+        PathBuf::from(".medkit-jobs").join("embedding-queue.mp")
+    }
+
+    /// Loads the persisted queue if one exists, otherwise starts empty.
+    pub fn load_or_new() -> Self {
+        let path = Self::queue_path();
+        let entries = fs::read(&path)
+            .ok()
+            .and_then(|bytes| rmp_serde::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        Self { path, entries: Mutex::new(entries) }
+    }
+
+    /// Jobs left `Pending` or `Failed` by a prior run, to be re-enqueued on
+    /// startup instead of silently dropped.
+    pub fn recoverable(&self) -> Vec<(u64, QueuedJob)> {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, job)| job.status != JobStatus::Done)
+            .map(|(hash, job)| (*hash, job.clone()))
+            .collect()
+    }
+
+    /// True if this exact chunk content was already embedded and posted
+    /// successfully, so dispatching it again would just pay for the same
+    /// vector twice.
+    pub fn is_done(&self, hash: u64) -> bool {
+        self.entries.lock().unwrap().get(&hash).map_or(false, |job| job.status == JobStatus::Done)
+    }
+
+    /// Records `hash` as dispatched-but-not-yet-confirmed, before the
+    /// embedding request is issued.
+    pub fn record_pending(&self, hash: u64, entity_id: String, chunk: String, port: u16) {
+        self.entries.lock().unwrap().insert(hash, QueuedJob { entity_id, chunk, port, status: JobStatus::Pending });
+        let _ = self.persist();
+    }
+
+    pub fn mark_done(&self, hash: u64) {
+        if let Some(job) = self.entries.lock().unwrap().get_mut(&hash) {
+            job.status = JobStatus::Done;
+        }
+        let _ = self.persist();
+    }
+
+    pub fn mark_failed(&self, hash: u64) {
+        if let Some(job) = self.entries.lock().unwrap().get_mut(&hash) {
+            job.status = JobStatus::Failed;
+        }
+        let _ = self.persist();
+    }
+
+    /// Serializes to MessagePack and writes atomically (temp file + rename)
+    /// so a crash mid-flush can't leave a corrupt queue behind.
+    fn persist(&self) -> Result<()> {
+        let entries = self.entries.lock().unwrap();
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let bytes = rmp_serde::to_vec(&*entries)?;
+        let tmp_path = self.path.with_extension("mp.tmp");
+        fs::write(&tmp_path, &bytes)?;
+        fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use indicatif::{ProgressBar, ProgressStyle};
+use serde::Serialize;
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::mpsc::{Receiver, Sender};
+
+/// Milestones emitted while `ingestion` walks a root, so a UI or CLI progress
+/// bar can observe how far a run has gotten instead of scraping stdout.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event")]
+pub enum IngestEvent {
+    RootCreated { root_id: String },
+    FolderCreated { name: String },
+    FileStarted { path: String },
+    FileSkipped { path: String, reason: String },
+    EntityIndexed { file_id: String },
+    ChunksEnqueued { count: usize },
+    FileCompleted { path: String },
+    Error { path: String, msg: String },
+    /// A whole run finished; carries the aggregate totals a consumer would
+    /// otherwise have to tally itself from every preceding event.
+    Finished { elapsed_secs: f64, files: usize, entities: usize, chunks: usize, errors: usize },
+}
+
+// Running totals complementing each other, so a consumer that didn't
+// register a `Sender<IngestEvent>` can still poll percentage-complete, and
+// so `ingestion` can stamp a `Finished` event with the run's final tallies.
+pub static TOTAL_FILES_SEEN: AtomicUsize = AtomicUsize::new(0);
+pub static FILES_COMPLETED: AtomicUsize = AtomicUsize::new(0);
+pub static CHUNKS_ENQUEUED: AtomicUsize = AtomicUsize::new(0);
+pub static ENTITIES_INDEXED: AtomicUsize = AtomicUsize::new(0);
+pub static ERRORS: AtomicUsize = AtomicUsize::new(0);
+
+/// Sends `event` on `events` if the caller registered a channel. Progress
+/// reporting is best-effort: a full or closed channel is dropped silently
+/// rather than slowing down or failing ingestion.
+pub fn emit(events: &Option<Sender<IngestEvent>>, event: IngestEvent) {
+    if let IngestEvent::Error { .. } = &event {
+        ERRORS.fetch_add(1, Ordering::SeqCst);
+    }
+    if let Some(tx) = events {
+        let _ = tx.try_send(event);
+    }
+}
+
+/// Resets every running total to zero, so a fresh run's `Finished` tallies
+/// aren't polluted by a previous one in the same process (e.g. the REPL's
+/// "Ingest" option run twice in a row).
+pub fn reset_counters() {
+    TOTAL_FILES_SEEN.store(0, Ordering::SeqCst);
+    FILES_COMPLETED.store(0, Ordering::SeqCst);
+    CHUNKS_ENQUEUED.store(0, Ordering::SeqCst);
+    ENTITIES_INDEXED.store(0, Ordering::SeqCst);
+    ERRORS.store(0, Ordering::SeqCst);
+}
+
+/// Drains `events` on a background task and renders a live progress bar plus
+/// a final summary line, replacing the scattered `println!`/`eprintln!` calls
+/// that used to narrate `populate`/`process_file` directly. Errors are
+/// surfaced as they arrive rather than buried in the scrollback.
+pub fn spawn_console_consumer(mut events: Receiver<IngestEvent>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let bar = ProgressBar::new_spinner();
+        bar.set_style(
+            ProgressStyle::with_template("{spinner} {msg}")
+                .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+        );
+
+        while let Some(event) = events.recv().await {
+            match event {
+                IngestEvent::RootCreated { root_id } => {
+                    bar.set_message(format!("root {} created", root_id));
+                }
+                IngestEvent::FolderCreated { name } => {
+                    bar.set_message(format!("indexing folder {}", name));
+                }
+                IngestEvent::FileStarted { path } => {
+                    bar.set_message(format!("processing {}", path));
+                }
+                IngestEvent::FileSkipped { path, reason } => {
+                    bar.set_message(format!("skipped {} ({})", path, reason));
+                }
+                IngestEvent::Error { path, msg } => {
+                    bar.println(format!("error: {}: {}", path, msg));
+                }
+                IngestEvent::Finished { elapsed_secs, files, entities, chunks, errors } => {
+                    bar.finish_and_clear();
+                    println!(
+                        "Indexed {} file(s), {} entitie(s), {} chunk(s) in {:.2}s ({} error(s))",
+                        files, entities, chunks, elapsed_secs, errors,
+                    );
+                }
+                IngestEvent::EntityIndexed { .. } | IngestEvent::ChunksEnqueued { .. } | IngestEvent::FileCompleted { .. } => {
+                    bar.tick();
+                }
+            }
+        }
+    })
+}
+
+/// Drains `events` on a background task, writing one JSON object per line to
+/// `writer` for a caller (a log aggregator, a TUI, another process) that
+/// wants machine-readable progress instead of the console consumer's
+/// human-oriented summary.
+pub fn spawn_jsonl_consumer<W: Write + Send + 'static>(
+    mut events: Receiver<IngestEvent>,
+    mut writer: W,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        while let Some(event) = events.recv().await {
+            match serde_json::to_string(&event) {
+                Ok(line) => {
+                    if writeln!(writer, "{}", line).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => eprintln!("Failed to serialize ingest event: {}", e),
+            }
+        }
+    })
+}
@@ -0,0 +1,56 @@
+use anyhow::Result;
+use git2::{Delta, DiffOptions, Oid, Repository};
+use std::path::{Path, PathBuf};
+
+/// Paths that changed between the last indexed commit and the current
+/// working tree (including staged and unstaged edits), split by whether
+/// the path still exists.
+#[derive(Debug, Default)]
+pub struct GitChanges {
+    pub changed: Vec<PathBuf>,
+    pub deleted: Vec<PathBuf>,
+}
+
+/// Opens the Git repository containing `root_path`, or `None` if it isn't
+/// under version control. `update` falls back to a full walk in that case.
+pub fn open_repo(root_path: &Path) -> Option<Repository> {
+    Repository::discover(root_path).ok()
+}
+
+pub fn head_sha(repo: &Repository) -> Result<String> {
+    let head = repo.head()?.peel_to_commit()?;
+    Ok(head.id().to_string())
+}
+
+/// Diffs `since_sha` against HEAD plus the working tree/index, so renames,
+/// edits made since the last index run, and uncommitted changes are all
+/// picked up in one pass.
+pub fn changes_since(repo: &Repository, since_sha: &str, root_path: &Path) -> Result<GitChanges> {
+    let since_oid = Oid::from_str(since_sha)?;
+    let since_tree = repo.find_commit(since_oid)?.tree()?;
+
+    let mut opts = DiffOptions::new();
+    opts.include_untracked(true);
+    let diff = repo.diff_tree_to_workdir_with_index(Some(&since_tree), Some(&mut opts))?;
+
+    let mut changes = GitChanges::default();
+    diff.foreach(
+        &mut |delta, _progress| {
+            let relative = delta.new_file().path().or_else(|| delta.old_file().path());
+            if let Some(relative) = relative {
+                let full_path = root_path.join(relative);
+                if delta.status() == Delta::Deleted {
+                    changes.deleted.push(full_path);
+                } else {
+                    changes.changed.push(full_path);
+                }
+            }
+            true
+        },
+        None,
+        None,
+        None,
+    )?;
+
+    Ok(changes)
+}
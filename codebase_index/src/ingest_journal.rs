@@ -0,0 +1,81 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+/// Durable record of an ingestion run's progress, keyed by the canonical
+/// path of each file/folder already created on the server. `populate` and
+/// `process_file` consult this before issuing a `createFile`/`createFolder`
+/// request so a crash mid-run doesn't duplicate server-side entities on
+/// restart.
+///
+/// Completion is subtree-granular for folders: a folder is only marked
+/// complete once it and everything under it has finished, so the journal
+/// stays a flat path -> hash map rather than also needing to cache every
+/// folder's server-side id across runs. A folder whose ingestion was
+/// interrupted partway is redone in full on resume.
+#[derive(Serialize, Deserialize)]
+pub struct JobState {
+    pub job_id: String,
+    pub root_path: PathBuf,
+    pub completed: HashMap<PathBuf, u64>,
+}
+
+impl JobState {
+    fn journal_path(root_path: &Path) -> PathBuf {
+        let root_name = root_path.file_name().and_then(|s| s.to_str()).unwrap_or("root");
+        PathBuf::from(".medkit-jobs").join(format!("{}.ingest.mp", root_name))
+    }
+
+    /// Loads the journal for `root_path` if one exists and matches, otherwise
+    /// starts a fresh job under a new id.
+    pub fn load_or_new(root_path: PathBuf) -> Self {
+        let journal_path = Self::journal_path(&root_path);
+        if let Ok(bytes) = fs::read(&journal_path) {
+            if let Ok(state) = rmp_serde::from_slice::<JobState>(&bytes) {
+                if state.root_path == root_path {
+                    return state;
+                }
+            }
+        }
+        JobState {
+            job_id: format!("{:x}", hash_contents(root_path.to_string_lossy().as_bytes())),
+            root_path,
+            completed: HashMap::new(),
+        }
+    }
+
+    /// Whether `path` was already ingested with this exact content hash.
+    pub fn is_complete(&self, path: &Path, hash: u64) -> bool {
+        self.completed.get(path).map_or(false, |recorded| *recorded == hash)
+    }
+
+    pub fn mark_complete(&mut self, path: PathBuf, hash: u64) {
+        self.completed.insert(path, hash);
+    }
+
+    /// Serializes to MessagePack and writes atomically (temp file + rename)
+    /// so a crash mid-flush can't leave a corrupt journal behind.
+    pub fn persist(&self) -> Result<()> {
+        let journal_path = Self::journal_path(&self.root_path);
+        if let Some(parent) = journal_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let bytes = rmp_serde::to_vec(self)?;
+        let tmp_path = journal_path.with_extension("mp.tmp");
+        fs::write(&tmp_path, &bytes)?;
+        fs::rename(&tmp_path, &journal_path)?;
+        Ok(())
+    }
+}
+
+/// Content hash used both for file bytes and, as a stable stand-in, for a
+/// folder's name (folders have no content of their own to hash).
+pub fn hash_contents(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
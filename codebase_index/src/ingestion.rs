@@ -1,16 +1,25 @@
 use anyhow::Result;
+use chrono::Utc;
 use ignore::WalkBuilder;
-use futures::future::join_all;
+use futures::stream::{self, StreamExt};
 use serde_json::json;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
+use std::time::Duration;
 use std::sync::{
     atomic::{AtomicUsize, Ordering},
-    Arc
+    Arc, Mutex
 };
 use tokio::sync::mpsc::Sender;
-use tokio::task::JoinHandle;
+use tokio::sync::Semaphore;
 use crate::utils::CodeEntity;
+use crate::events::{emit, IngestEvent, CHUNKS_ENQUEUED, ENTITIES_INDEXED, FILES_COMPLETED, TOTAL_FILES_SEEN};
+use crate::ingest_journal::{hash_contents, JobState};
+use crate::reindex_manifest::{hash_text, EntityManifestEntry, ReindexManifest};
+use crate::updater::update_file;
+use crate::utils::delete_files;
+use crate::vfs::OsFs;
 use tree_sitter::{Node, Parser};
 #[derive(Clone)]
 pub struct OwnedNode {
@@ -23,17 +32,53 @@ pub struct OwnedNode {
 
 // Import from our modules
 use crate::utils::{
-    post_request_async, chunk_entity, get_language, EmbeddingJob, TOTAL_CHUNKS
+    post_request_async, post_request_with_retry, chunk_entity, chunk_entity_for_language, get_language, EmbeddingJob, TOTAL_CHUNKS
 };
+use crate::resilience::RetryPolicy;
 
 // Add use async_recursion::async_recursion;
 use async_recursion::async_recursion;
 
+/// Default number of `post_request_async` calls (folder/file/entity/chunk
+/// creation) allowed in flight at once across an ingestion run, used when a
+/// caller doesn't pick its own limit.
+pub const DEFAULT_INGEST_CONCURRENCY: usize = 32;
+
+/// Retry policy shared by every create-* call an ingestion run makes. A
+/// large walk is far more likely to hit a cold-starting or momentarily
+/// overloaded server than a one-off lookup, so it gets more attempts than
+/// `RetryPolicy::default()`, starting from a shorter base delay and capped
+/// at a lower maximum delay.
+const INGEST_RETRY_POLICY: RetryPolicy = RetryPolicy {
+    max_attempts: 6,
+    base_delay: Duration::from_millis(100),
+    max_delay: Duration::from_secs(10),
+};
+
+/// Acquires a permit from the shared concurrency limiter before issuing the
+/// request, so a large tree can't open more HTTP connections to the local
+/// server than `concurrency` at once, no matter how deep the recursion fans
+/// out underneath `populate`. Retries transient failures (connection resets,
+/// 5xx, a server still warming up) with exponential backoff and jitter under
+/// `INGEST_RETRY_POLICY`; a non-retryable 4xx fails immediately.
+async fn gated_post_request(
+    semaphore: &Arc<Semaphore>,
+    url: &str,
+    payload: serde_json::Value,
+) -> Result<serde_json::Value> {
+    let _permit = semaphore.clone().acquire_owned().await?;
+    post_request_with_retry(url, payload, INGEST_RETRY_POLICY).await
+}
+
 pub async fn ingestion(
     root_path: PathBuf,
     port: u16,
     tx: Sender<EmbeddingJob>,
+    concurrency: usize,
+    events: Option<Sender<IngestEvent>>,
 ) -> Result<String> {
+    let started_at = std::time::Instant::now();
+    crate::events::reset_counters();
     println!("Starting ingestion for directory: {}", root_path.display());
 
     // Create a root entry in the index
@@ -47,6 +92,7 @@ pub async fn ingestion(
         .ok_or_else(|| anyhow::anyhow!("Root ID not found"))?;
 
     println!("\nRoot created");
+    emit(&events, IngestEvent::RootCreated { root_id: root_id.to_string() });
 
     // Load index types
     let index_types = fs::read_to_string("index-types.json")?;
@@ -57,15 +103,101 @@ pub async fn ingestion(
     let file_types: serde_json::Value = serde_json::from_str(&file_types)?;
     let file_types = Arc::new(file_types);
 
+    // Resume from a prior run's journal, if one exists for this root, so a
+    // crash mid-ingestion doesn't re-create everything from scratch.
+    let job_state = Some(Arc::new(Mutex::new(JobState::load_or_new(root_path.clone()))));
+
+    // Load the content-hash manifest from a prior run, if any, so unchanged
+    // files are skipped and changed ones go through `updateFile` instead of
+    // recreating the file and every entity underneath it.
+    let manifest = Arc::new(Mutex::new(ReindexManifest::load_or_new(root_path.clone())));
+
+    // Bounds how many HTTP calls into the local server (and the file parses
+    // that feed them) are active at once, so a large monorepo doesn't spawn
+    // thousands of concurrent tasks and sockets.
+    let semaphore = Arc::new(Semaphore::new(concurrency));
+
     // Start populating the index with directory contents
     populate(
         root_path,root_id.to_string(),port,
-        true,index_types,file_types,tx
+        true,index_types,file_types,tx,job_state,Some(manifest.clone()),semaphore,concurrency,events,true,
     ).await?;
 
+    // Any path the manifest remembers that no longer exists on disk was
+    // removed since the last run; delete it (and its entities) server-side
+    // so the index doesn't keep serving stale content forever.
+    reconcile_deletions(&manifest, port).await;
+
+    emit(&events, IngestEvent::Finished {
+        elapsed_secs: started_at.elapsed().as_secs_f64(),
+        files: FILES_COMPLETED.load(Ordering::SeqCst),
+        entities: ENTITIES_INDEXED.load(Ordering::SeqCst),
+        chunks: CHUNKS_ENQUEUED.load(Ordering::SeqCst),
+        errors: crate::events::ERRORS.load(Ordering::SeqCst),
+    });
+
     Ok(root_id.to_string())
 }
 
+/// Deletes every file the manifest still has a record of but that vanished
+/// from disk between runs, then drops those entries from the manifest.
+async fn reconcile_deletions(manifest: &Arc<Mutex<ReindexManifest>>, port: u16) {
+    let stale: Vec<(PathBuf, String)> = {
+        let manifest = manifest.lock().unwrap();
+        manifest
+            .paths()
+            .into_iter()
+            .filter(|path| !path.exists())
+            .filter_map(|path| {
+                manifest.get(&path).map(|entry| (path, entry.file_id.clone()))
+            })
+            .collect()
+    };
+    if stale.is_empty() {
+        return;
+    }
+
+    println!("\nReconciling {} file(s) removed since the last ingest", stale.len());
+    for (path, file_id) in &stale {
+        let path_key = path.display().to_string();
+        let mut file_name_ids = HashMap::new();
+        file_name_ids.insert(path_key.clone(), (file_id.clone(), String::new()));
+        if let Err(e) = delete_files(vec![path_key], file_name_ids, port).await {
+            eprintln!("Failed to delete removed file {}: {}", path.display(), e);
+            continue;
+        }
+        manifest.lock().unwrap().remove(path);
+    }
+    if let Err(e) = manifest.lock().unwrap().persist() {
+        eprintln!("Failed to persist reindex manifest after deletion reconciliation: {}", e);
+    }
+}
+
+/// Indexes just `path`'s immediate contents (its files and folder records)
+/// without descending into any subfolders, for fast targeted updates of a
+/// known subtree instead of a full-tree walk (e.g. after dropping a few
+/// files into one folder). Thin wrapper around `populate` with `recursive`
+/// set to `false`.
+pub async fn ingest_shallow(
+    path: PathBuf,
+    parent_id: String,
+    port: u16,
+    tx: Sender<EmbeddingJob>,
+) -> Result<()> {
+    let index_types = fs::read_to_string("index-types.json")?;
+    let index_types: Arc<serde_json::Value> = Arc::new(serde_json::from_str(&index_types)?);
+
+    let file_types = fs::read_to_string("file_types.json")?;
+    let file_types: Arc<serde_json::Value> = Arc::new(serde_json::from_str(&file_types)?);
+
+    let semaphore = Arc::new(Semaphore::new(DEFAULT_INGEST_CONCURRENCY));
+
+    populate(
+        path, parent_id, port, false, index_types, file_types, tx, None, None,
+        semaphore, DEFAULT_INGEST_CONCURRENCY, None, false,
+    ).await
+}
+
 /// Recursively populates the index with directory contents
 #[async_recursion]
 pub async fn populate(
@@ -76,6 +208,12 @@ pub async fn populate(
     index_types: Arc<serde_json::Value>,
     file_types: Arc<serde_json::Value>,
     tx: Sender<EmbeddingJob>,
+    job_state: Option<Arc<Mutex<JobState>>>,
+    manifest: Option<Arc<Mutex<ReindexManifest>>>,
+    semaphore: Arc<Semaphore>,
+    concurrency: usize,
+    events: Option<Sender<IngestEvent>>,
+    recursive: bool,
 ) -> Result<()> {
     // Initialize walker builder
     let mut walker_builder = WalkBuilder::new(&current_path);
@@ -92,18 +230,36 @@ pub async fn populate(
         .filter(|entry| entry.path() != current_path)
         .collect();
 
-    // Process entries concurrently
-    let tasks: Vec<JoinHandle<Result<()>>> = entries.into_iter().map(|entry| {
+    // Process entries with at most `concurrency` active at once, so a wide
+    // directory doesn't fan out thousands of tasks in one go.
+    let results: Vec<Result<()>> = stream::iter(entries).map(|entry| {
         let path_buf = entry.path().to_path_buf();
         let parent_id_clone = parent_id.clone();
         let index_types_clone = index_types.clone();
         let file_types_clone = file_types.clone();
         let tx_clone = tx.clone();
+        let job_state_clone = job_state.clone();
+        let manifest_clone = manifest.clone();
+        let semaphore_clone = semaphore.clone();
+        let events_clone = events.clone();
 
-        tokio::spawn(async move {
+        async move {
             if path_buf.is_dir() {
                 // Get folder information
                 let folder_name = path_buf.file_name().unwrap().to_str().unwrap();
+
+                // A folder is only marked complete once it and everything
+                // inside it finished on a prior run, so a completed marker
+                // here means nothing left to do.
+                let folder_hash = hash_contents(folder_name.as_bytes());
+                let already_complete = job_state_clone.as_ref().map_or(false, |state| {
+                    state.lock().unwrap().is_complete(&path_buf, folder_hash)
+                });
+                if already_complete {
+                    println!("\nSkipping already-ingested folder: {}", folder_name);
+                    return Ok(());
+                }
+
                 let endpoint = if is_super {"createSuperFolder"} else {"createSubFolder"};
                 let url = format!("http://localhost:{}/{}", port, endpoint);
                 let payload = if is_super {
@@ -113,8 +269,7 @@ pub async fn populate(
                 };
 
                 // Send request to create folder and get its ID
-                println!("\nSubmitting {} folder for processing", folder_name);
-                match post_request_async(&url, payload).await {
+                match gated_post_request(&semaphore_clone, &url, payload).await {
                     Ok(res) => {
                         if let Some(folder_id) = res
                             .get(if is_super { "folder" } else { "subfolder" })
@@ -122,37 +277,62 @@ pub async fn populate(
                             .and_then(|v| v.as_str())
                             .map(|s| s.to_string())
                         {
+                            emit(&events_clone, IngestEvent::FolderCreated { name: folder_name.to_string() });
+                            if !recursive {
+                                // Shallow mode: the folder record is created,
+                                // but its contents are left for a future
+                                // targeted or full walk to pick up.
+                                return Ok(());
+                            }
                             let path_buf_clone = path_buf.clone();
-                            if let Err(e) = Box::pin(populate(
+                            let job_state_inner = job_state_clone.clone();
+                            let manifest_inner = manifest_clone.clone();
+                            match Box::pin(populate(
                                 path_buf_clone,folder_id,port,
-                                false,index_types_clone, file_types_clone, tx_clone
+                                false,index_types_clone, file_types_clone, tx_clone, job_state_inner, manifest_inner,
+                                semaphore_clone.clone(), concurrency, events_clone.clone(), recursive,
                             )).await {
-                                eprintln!("Error populating folder {}: {}",folder_name, e);
+                                Ok(()) => {
+                                    if let Some(state) = &job_state_clone {
+                                        let mut state = state.lock().unwrap();
+                                        state.mark_complete(path_buf.clone(), folder_hash);
+                                        if let Err(e) = state.persist() {
+                                            eprintln!("Failed to persist ingestion journal: {}", e);
+                                        }
+                                    }
+                                }
+                                Err(e) => {
+                                    emit(&events_clone, IngestEvent::Error { path: path_buf.display().to_string(), msg: e.to_string() });
+                                }
                             }
                             Ok(())
                         } else {
-                            eprintln!("Failed to extract folder ID from response for: {}", folder_name);
+                            emit(&events_clone, IngestEvent::Error {
+                                path: path_buf.display().to_string(),
+                                msg: format!("folder ID not found in response for {}", folder_name),
+                            });
                             Ok(())
                         }
                     }
                     Err(e) => {
-                        eprintln!("Failed to create folder {}: {}", folder_name, e);
+                        emit(&events_clone, IngestEvent::Error { path: path_buf.display().to_string(), msg: e.to_string() });
                         Ok(())
                     }
                 }
             } else if path_buf.is_file() {
                 process_file(
                     path_buf,parent_id_clone,is_super,
-                    port, index_types_clone,file_types_clone,tx_clone
+                    port, index_types_clone,file_types_clone,tx_clone,job_state_clone,manifest_clone,
+                    semaphore_clone, concurrency, events_clone,
                 ).await
             } else {
                 Ok(())
             }
-        })
-    }).collect();
+        }
+    }).buffer_unordered(concurrency).collect().await;
 
-    for task in tasks {
-        task.await??;
+    for result in results {
+        result?;
     }
 
     Ok(())
@@ -167,22 +347,163 @@ pub async fn process_file(
     index_types: Arc<serde_json::Value>,
     file_types: Arc<serde_json::Value>,
     tx: Sender<EmbeddingJob>,
+    job_state: Option<Arc<Mutex<JobState>>>,
+    manifest: Option<Arc<Mutex<ReindexManifest>>>,
+    semaphore: Arc<Semaphore>,
+    concurrency: usize,
+    events: Option<Sender<IngestEvent>>,
 ) -> Result<()> {
+    TOTAL_FILES_SEEN.fetch_add(1, Ordering::SeqCst);
+    emit(&events, IngestEvent::FileStarted { path: file_path.display().to_string() });
+
     // Read file contents
     let source_code = match fs::read_to_string(&file_path) {
         Ok(source_code) => source_code,
         Err(e) => {
-            eprintln!("Skipped {}: {}", file_path.file_name().unwrap().to_str().unwrap(), e);
+            emit(&events, IngestEvent::Error { path: file_path.display().to_string(), msg: e.to_string() });
             return Ok(());
         }
     };
 
+    // Skip files whose completion marker is present and whose content hash
+    // still matches, so a resumed run doesn't duplicate server-side entities.
+    let content_hash = hash_contents(source_code.as_bytes());
+    let already_complete = job_state.as_ref().map_or(false, |state| {
+        state.lock().unwrap().is_complete(&file_path, content_hash)
+    });
+    if already_complete {
+        emit(&events, IngestEvent::FileSkipped {
+            path: file_path.display().to_string(),
+            reason: "already ingested".to_string(),
+        });
+        return Ok(());
+    }
+
     let file_name = file_path.file_name().unwrap().to_str().unwrap();
     let extension = file_path
         .extension()
         .and_then(|s| s.to_str())
         .unwrap_or("txt");
 
+    // Check the cross-run manifest for a file we've already ingested at this
+    // path, so a warm re-ingestion can skip it outright when unchanged, or
+    // `updateFile` it in place when changed instead of recreating it (and
+    // every entity under it) from scratch.
+    let text_hash = hash_text(&source_code);
+    let prior_entry = manifest.as_ref().and_then(|m| m.lock().unwrap().get(&file_path).cloned());
+
+    if let Some(prior) = &prior_entry {
+        if prior.hash == text_hash {
+            emit(&events, IngestEvent::FileSkipped {
+                path: file_path.display().to_string(),
+                reason: "unchanged since last ingest".to_string(),
+            });
+            if let Some(state) = &job_state {
+                let mut state = state.lock().unwrap();
+                state.mark_complete(file_path.clone(), content_hash);
+                if let Err(e) = state.persist() {
+                    eprintln!("Failed to persist ingestion journal: {}", e);
+                }
+            }
+            return Ok(());
+        }
+
+        // Changed since last run, but if every top-level entity's content is
+        // byte-for-byte identical to what's already indexed (a change
+        // outside any top-level node — whitespace between them, a trailing
+        // newline), nothing downstream actually needs re-embedding. Refresh
+        // just the stored file text and hash and leave every entity alone.
+        if !prior.entities.is_empty() {
+            if let Some(language) = get_language(&file_path) {
+                let mut parser = Parser::new();
+                parser.set_language(&language)?;
+                if let Some(tree) = parser.parse(&source_code, None) {
+                    let owned_nodes = build_owned_nodes(tree.root_node(), &source_code);
+                    let current_hashes: HashSet<String> =
+                        owned_nodes.iter().map(|n| hash_text(&n.text)).collect();
+                    let prior_hashes: HashSet<String> =
+                        prior.entities.iter().map(|e| e.hash.clone()).collect();
+                    if current_hashes == prior_hashes {
+                        let url = format!("http://localhost:{}/{}", port, "updateFile");
+                        let payload = json!({
+                            "file_id": prior.file_id,
+                            "text": source_code,
+                            "extracted_at": Utc::now().to_rfc3339(),
+                        });
+                        if let Err(e) = gated_post_request(&semaphore, &url, payload).await {
+                            emit(&events, IngestEvent::Error { path: file_path.display().to_string(), msg: e.to_string() });
+                        }
+                        if let Some(m) = &manifest {
+                            let mut m = m.lock().unwrap();
+                            m.record(file_path.clone(), text_hash.clone(), prior.file_id.clone(), prior.entities.clone());
+                            if let Err(e) = m.persist() {
+                                eprintln!("Failed to persist reindex manifest: {}", e);
+                            }
+                        }
+                        if let Some(state) = &job_state {
+                            let mut state = state.lock().unwrap();
+                            state.mark_complete(file_path.clone(), content_hash);
+                            if let Err(e) = state.persist() {
+                                eprintln!("Failed to persist ingestion journal: {}", e);
+                            }
+                        }
+                        FILES_COMPLETED.fetch_add(1, Ordering::SeqCst);
+                        emit(&events, IngestEvent::FileCompleted { path: file_path.display().to_string() });
+                        return Ok(());
+                    }
+                }
+            }
+        }
+
+        // Changed since last run: update the existing file and its entities
+        // in place through the same reconciliation path `update` already
+        // uses, rather than creating a duplicate File node.
+        update_file(
+            file_path.clone(), prior.file_id.clone(), port,
+            index_types.clone(), file_types.clone(), tx.clone(), Arc::new(OsFs), concurrency,
+        ).await?;
+
+        if let Some(m) = &manifest {
+            // Recompute the entity manifest from the new source so the
+            // entity-inert fast path above (gated on `!prior.entities.is_empty()`)
+            // stays available on future runs — recording an empty list here
+            // would permanently disqualify this file from that optimization
+            // after its first substantive edit.
+            let entity_manifest = get_language(&file_path)
+                .and_then(|language| {
+                    let mut parser = Parser::new();
+                    parser.set_language(&language).ok()?;
+                    parser.parse(&source_code, None)
+                })
+                .map(|tree| {
+                    build_owned_nodes(tree.root_node(), &source_code)
+                        .iter()
+                        .map(|n| EntityManifestEntry {
+                            start_byte: n.start_byte,
+                            end_byte: n.end_byte,
+                            hash: hash_text(&n.text),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            let mut m = m.lock().unwrap();
+            m.record(file_path.clone(), text_hash.clone(), prior.file_id.clone(), entity_manifest);
+            if let Err(e) = m.persist() {
+                eprintln!("Failed to persist reindex manifest: {}", e);
+            }
+        }
+        if let Some(state) = &job_state {
+            let mut state = state.lock().unwrap();
+            state.mark_complete(file_path.clone(), content_hash);
+            if let Err(e) = state.persist() {
+                eprintln!("Failed to persist ingestion journal: {}", e);
+            }
+        }
+        FILES_COMPLETED.fetch_add(1, Ordering::SeqCst);
+        emit(&events, IngestEvent::FileCompleted { path: file_path.display().to_string() });
+        return Ok(());
+    }
+
     let supported = file_types.get("supported").unwrap().as_array().unwrap();
     let unsupported = file_types.get("unsupported").unwrap().as_array().unwrap();
 
@@ -194,7 +515,6 @@ pub async fn process_file(
         let tree = parser.parse(&source_code, None).unwrap();
 
         // Create file
-        let file_type = if is_super { "super" } else { "sub" };
         let endpoint = if is_super {"createSuperFile"} else {"createFile"};
         let url = format!("http://localhost:{}/{}", port, endpoint);
         let payload = if is_super {
@@ -204,17 +524,19 @@ pub async fn process_file(
         };
 
         // Send request to create file
-        println!("\nProcessing {} file: {}", file_type, file_name);
-        let file_response = match post_request_async(&url, payload).await {
+        let file_response = match gated_post_request(&semaphore, &url, payload).await {
             Ok(response) => response,
             Err(e) => {
-                eprintln!("Failed to create file {}: {}", file_name, e);
+                emit(&events, IngestEvent::Error { path: file_path.display().to_string(), msg: e.to_string() });
                 return Err(anyhow::anyhow!("Failed to create file: {}", e));
             }
         };
 
         if !supported.iter().any(|v| v.as_str().map_or(false, |s| s == extension || s == "ALL")){
-            println!("File {} is skipped", file_name);
+            emit(&events, IngestEvent::FileSkipped {
+                path: file_path.display().to_string(),
+                reason: "extension not in supported index types".to_string(),
+            });
             return Ok(());
         }
 
@@ -223,14 +545,46 @@ pub async fn process_file(
             .and_then(|v| v.get("id"))
             .and_then(|v| v.as_str())
             .ok_or_else(|| {
-                eprintln!("Failed to extract file ID from response for: {}", file_name);
+                emit(&events, IngestEvent::Error {
+                    path: file_path.display().to_string(),
+                    msg: format!("file ID not found in response for {}", file_name),
+                });
                 anyhow::anyhow!("File ID not found in response")
             })?;
 
         // Process entities
         let root_node = tree.root_node();
         let owned_nodes = build_owned_nodes(root_node, &source_code);
-        ingest_entities(owned_nodes, file_id.to_string(), port, extension.to_string(), index_types, tx).await?;
+        let entity_manifest: Vec<EntityManifestEntry> = owned_nodes
+            .iter()
+            .map(|n| EntityManifestEntry {
+                start_byte: n.start_byte,
+                end_byte: n.end_byte,
+                hash: hash_text(&n.text),
+            })
+            .collect();
+        let file_id = file_id.to_string();
+        ingest_entities(
+            owned_nodes, file_id.clone(), port, extension.to_string(), index_types, tx,
+            semaphore.clone(), concurrency, events.clone(), Some(language.clone()),
+        ).await?;
+
+        if let Some(m) = &manifest {
+            let mut m = m.lock().unwrap();
+            m.record(file_path.clone(), text_hash.clone(), file_id.clone(), entity_manifest);
+            if let Err(e) = m.persist() {
+                eprintln!("Failed to persist reindex manifest: {}", e);
+            }
+        }
+        if let Some(state) = &job_state {
+            let mut state = state.lock().unwrap();
+            state.mark_complete(file_path.clone(), content_hash);
+            if let Err(e) = state.persist() {
+                eprintln!("Failed to persist ingestion journal: {}", e);
+            }
+        }
+        FILES_COMPLETED.fetch_add(1, Ordering::SeqCst);
+        emit(&events, IngestEvent::FileCompleted { path: file_path.display().to_string() });
     } else {
         // Create file without entities
         let endpoint = if is_super {"createSuperFile"} else {"createFile"};
@@ -242,21 +596,56 @@ pub async fn process_file(
         };
 
         // Send request to create file
-        println!("\nProcessing unsupported file: {}", file_name);
-        let response = post_request_async(&url, payload).await?;
+        let response = match gated_post_request(&semaphore, &url, payload).await {
+            Ok(response) => response,
+            Err(e) => {
+                emit(&events, IngestEvent::Error { path: file_path.display().to_string(), msg: e.to_string() });
+                return Err(e);
+            }
+        };
 
         if !unsupported.iter().any(|v| v.as_str().map_or(false, |s| s == extension || s == "ALL")){
-            println!("File {} is skipped", file_name);
+            emit(&events, IngestEvent::FileSkipped {
+                path: file_path.display().to_string(),
+                reason: "extension not in unsupported index types".to_string(),
+            });
             return Ok(());
         }
 
-        let file_id = response.get("file").and_then(|v| v.get("id")).and_then(|v| v.as_str()).ok_or_else(|| anyhow::anyhow!("File ID not found"))?;
+        let file_id = response.get("file").and_then(|v| v.get("id")).and_then(|v| v.as_str()).ok_or_else(|| {
+            emit(&events, IngestEvent::Error {
+                path: file_path.display().to_string(),
+                msg: "file ID not found in response".to_string(),
+            });
+            anyhow::anyhow!("File ID not found")
+        })?;
 
         let chunks = chunk_entity(&source_code).unwrap();
         let order_counter = Arc::new(AtomicUsize::new(1));
         TOTAL_CHUNKS.fetch_add(chunks.len(), Ordering::SeqCst);
-
-        process_unsupported_file(chunks, file_id.to_string(), port, order_counter, tx).await?;
+        CHUNKS_ENQUEUED.fetch_add(chunks.len(), Ordering::SeqCst);
+        emit(&events, IngestEvent::ChunksEnqueued { count: chunks.len() });
+
+        process_unsupported_file(
+            chunks, file_id.to_string(), port, order_counter, tx, semaphore.clone(), concurrency, events.clone(),
+        ).await?;
+
+        if let Some(m) = &manifest {
+            let mut m = m.lock().unwrap();
+            m.record(file_path.clone(), text_hash.clone(), file_id.to_string(), Vec::new());
+            if let Err(e) = m.persist() {
+                eprintln!("Failed to persist reindex manifest: {}", e);
+            }
+        }
+        if let Some(state) = &job_state {
+            let mut state = state.lock().unwrap();
+            state.mark_complete(file_path.clone(), content_hash);
+            if let Err(e) = state.persist() {
+                eprintln!("Failed to persist ingestion journal: {}", e);
+            }
+        }
+        FILES_COMPLETED.fetch_add(1, Ordering::SeqCst);
+        emit(&events, IngestEvent::FileCompleted { path: file_path.display().to_string() });
     }
     Ok(())
 }
@@ -267,13 +656,18 @@ pub async fn process_unsupported_file(
     port: u16,
     order_counter: Arc<AtomicUsize>,
     tx: Sender<EmbeddingJob>,
+    semaphore: Arc<Semaphore>,
+    concurrency: usize,
+    events: Option<Sender<IngestEvent>>,
 ) -> Result<()> {
-    let tasks: Vec<JoinHandle<()>> = chunks.into_iter().map(|chunk| {
+    stream::iter(chunks).map(|chunk| {
         let file_id_clone = file_id.clone();
         let order_counter_clone = order_counter.clone();
         let tx_clone = tx.clone();
+        let semaphore_clone = semaphore.clone();
+        let events_clone = events.clone();
 
-        tokio::spawn(async move {
+        async move {
             let url = format!("http://localhost:{}/{}", port, "createSuperEntity");
             let payload = json!({
                     "file_id": file_id_clone,
@@ -285,18 +679,18 @@ pub async fn process_unsupported_file(
                 });
 
             // Send request to create entity
-            let entity_response = post_request_async(&url, payload).await;
+            let entity_response = gated_post_request(&semaphore_clone, &url, payload).await;
             let entity_id = match entity_response {
                 Ok(response) => response.get("entity")
                     .and_then(|v| v.get("id"))
                     .and_then(|v| v.as_str())
                     .map(|s| s.to_string()),
                 Err(e) => {
-                    eprintln!("Failed to create entity: {}", e);
+                    emit(&events_clone, IngestEvent::Error { path: file_id_clone.clone(), msg: e.to_string() });
                     None
                 }
             };
-            
+
             // Generate embedding
             if let Some(entity_id) = entity_id {
                 let job = EmbeddingJob {chunk: chunk.clone(), entity_id, port};
@@ -314,10 +708,8 @@ pub async fn process_unsupported_file(
                     }
                 }
             }
-        })
-    }).collect();
-
-    join_all(tasks).await;
+        }
+    }).buffer_unordered(concurrency).for_each(|_| async {}).await;
 
     Ok(())
 }
@@ -329,15 +721,22 @@ pub async fn ingest_entities(
     extension: String,
     index_types: Arc<serde_json::Value>,
     tx: Sender<EmbeddingJob>,
+    semaphore: Arc<Semaphore>,
+    concurrency: usize,
+    events: Option<Sender<IngestEvent>>,
+    language: Option<tree_sitter::Language>,
 ) -> Result<()> {
     let order_counter = Arc::new(AtomicUsize::new(1));
-    let tasks: Vec<JoinHandle<Result<()>>> = owned_nodes.into_iter().map(|owned| {
+    let results: Vec<Result<()>> = stream::iter(owned_nodes).map(|owned| {
         let file_id_clone = file_id.clone();
         let extension_clone = extension.clone();
         let index_types_clone = index_types.clone();
         let tx_clone = tx.clone();
         let order_counter_clone = order_counter.clone();
-        tokio::spawn(async move {
+        let semaphore_clone = semaphore.clone();
+        let events_clone = events.clone();
+        let language_clone = language.clone();
+        async move {
             let current_order = order_counter_clone.fetch_add(1, Ordering::SeqCst);
             // Get index_types for file extension
             if let Some(types) = index_types_clone.get(&extension_clone) {
@@ -360,15 +759,20 @@ pub async fn ingest_entities(
                                 "order": current_order,
                             });
                             // Send request
-                            if let Ok(entity_response) = post_request_async(&url, payload).await {
+                            if let Ok(entity_response) = gated_post_request(&semaphore_clone, &url, payload).await {
                                 if let Some(entity_id) = entity_response.get("entity").and_then(|v| v.get("id")).and_then(|v| v.as_str()) {
-                                    if let Ok(chunks) = chunk_entity(entity_content) {
+                                    ENTITIES_INDEXED.fetch_add(1, Ordering::SeqCst);
+                                    emit(&events_clone, IngestEvent::EntityIndexed { file_id: file_id_clone.clone() });
+                                    if let Ok(chunks) = chunk_entity_for_language(entity_content, language_clone.clone()) {
                                         TOTAL_CHUNKS.fetch_add(chunks.len(), Ordering::SeqCst);
-                                        let chunk_tasks: Vec<JoinHandle<()>> = chunks.into_iter().map(|chunk| {
+                                        CHUNKS_ENQUEUED.fetch_add(chunks.len(), Ordering::SeqCst);
+                                        emit(&events_clone, IngestEvent::ChunksEnqueued { count: chunks.len() });
+                                        let entity_id = entity_id.to_string();
+                                        stream::iter(chunks).map(|chunk| {
                                             let chunk_clone = chunk.clone();
-                                            let entity_id_clone = entity_id.to_string();
+                                            let entity_id_clone = entity_id.clone();
                                             let tx_clone_inner = tx_clone.clone();
-                                            tokio::spawn(async move {
+                                            async move {
                                                 let job = EmbeddingJob {chunk: chunk_clone, entity_id: entity_id_clone, port};
                                                 match tx_clone_inner.try_send(job) {
                                                     Ok(_) => {},
@@ -382,9 +786,8 @@ pub async fn ingest_entities(
                                                         });
                                                     }
                                                 }
-                                            })
-                                        }).collect();
-                                        join_all(chunk_tasks).await;
+                                            }
+                                        }).buffer_unordered(concurrency).for_each(|_| async {}).await;
                                     }
                                 }
                             }
@@ -392,11 +795,14 @@ pub async fn ingest_entities(
                     }
                 }
             }
-            process_entity(owned, file_id_clone, port, true, current_order, extension_clone, index_types_clone, tx_clone).await
-        })
-    }).collect();
-    for task in tasks {
-        task.await??;
+            process_entity(
+                owned, file_id_clone.clone(), port, true, current_order, extension_clone, index_types_clone, tx_clone,
+                semaphore_clone, concurrency, file_id_clone, events_clone, language_clone,
+            ).await
+        }
+    }).buffer_unordered(concurrency).collect().await;
+    for result in results {
+        result?;
     }
     Ok(())
 }
@@ -412,6 +818,11 @@ async fn process_entity(
     extension: String,
     index_types: Arc<serde_json::Value>,
     tx: Sender<EmbeddingJob>,
+    semaphore: Arc<Semaphore>,
+    concurrency: usize,
+    file_id: String,
+    events: Option<Sender<IngestEvent>>,
+    language: Option<tree_sitter::Language>,
 ) -> Result<()> {
     let code_entity = CodeEntity {
         entity_type: owned.kind.clone(),
@@ -423,7 +834,10 @@ async fn process_entity(
     if extension == "py" && code_entity.entity_type == "block" && !owned.children.is_empty() {
         let mut order = 1;
         for child in owned.children.into_iter() {
-            process_entity(child, parent_id.clone(), port, false, order, extension.clone(), index_types.clone(), tx.clone()).await?;
+            process_entity(
+                child, parent_id.clone(), port, false, order, extension.clone(), index_types.clone(), tx.clone(),
+                semaphore.clone(), concurrency, file_id.clone(), events.clone(), language.clone(),
+            ).await?;
             order += 1;
         }
     } else {
@@ -452,21 +866,25 @@ async fn process_entity(
                         "end_byte": code_entity.end_byte,
                         "order": code_entity.order,
                     });
-                    let entity_response = post_request_async(&url, payload).await?;
+                    let entity_response = gated_post_request(&semaphore, &url, payload).await?;
                     let entity_id = entity_response
                         .get("entity")
                         .and_then(|v| v.get("id"))
                         .and_then(|v| v.as_str())
                         .map(|s| s.to_string())
                         .ok_or_else(|| anyhow::anyhow!("Entity ID not found"))?;
+                    ENTITIES_INDEXED.fetch_add(1, Ordering::SeqCst);
+                    emit(&events, IngestEvent::EntityIndexed { file_id: file_id.clone() });
                     if is_super {
-                        let chunks = chunk_entity(&code_entity.text).unwrap();
+                        let chunks = chunk_entity_for_language(&code_entity.text, language.clone()).unwrap();
                         TOTAL_CHUNKS.fetch_add(chunks.len(), Ordering::SeqCst);
-                        let chunk_tasks: Vec<JoinHandle<()>> = chunks.into_iter().map(|chunk| {
+                        CHUNKS_ENQUEUED.fetch_add(chunks.len(), Ordering::SeqCst);
+                        emit(&events, IngestEvent::ChunksEnqueued { count: chunks.len() });
+                        stream::iter(chunks).map(|chunk| {
                             let chunk_clone = chunk.clone();
                             let entity_id_clone = entity_id.clone();
                             let tx_clone = tx.clone();
-                            tokio::spawn(async move {
+                            async move {
                                 let job = EmbeddingJob {chunk: chunk_clone, entity_id: entity_id_clone, port};
                                 match tx_clone.try_send(job) {
                                     Ok(_) => {},
@@ -480,25 +898,31 @@ async fn process_entity(
                                         });
                                     }
                                 }
-                            })
-                        }).collect();
-                        join_all(chunk_tasks).await;
+                            }
+                        }).buffer_unordered(concurrency).for_each(|_| async {}).await;
                     }
                     if !owned.children.is_empty() {
                         let order_counter = Arc::new(AtomicUsize::new(1));
-                        let child_tasks: Vec<JoinHandle<Result<()>>> = owned.children.into_iter().map(|child| {
+                        let results: Vec<Result<()>> = stream::iter(owned.children).map(|child| {
                             let entity_id_clone = entity_id.clone();
                             let extension_clone = extension.clone();
                             let index_types_clone = index_types.clone();
                             let tx_clone = tx.clone();
                             let order_counter_clone = order_counter.clone();
-                            tokio::spawn(async move {
+                            let semaphore_clone = semaphore.clone();
+                            let file_id_clone = file_id.clone();
+                            let events_clone = events.clone();
+                            let language_clone = language.clone();
+                            async move {
                                 let current_order = order_counter_clone.fetch_add(1, Ordering::SeqCst);
-                                process_entity(child, entity_id_clone, port, false, current_order, extension_clone, index_types_clone, tx_clone).await
-                            })
-                        }).collect();
-                        for task in child_tasks {
-                            task.await??;
+                                process_entity(
+                                    child, entity_id_clone, port, false, current_order, extension_clone, index_types_clone, tx_clone,
+                                    semaphore_clone, concurrency, file_id_clone, events_clone, language_clone,
+                                ).await
+                            }
+                        }).buffer_unordered(concurrency).collect().await;
+                        for result in results {
+                            result?;
                         }
                     }
                 }
@@ -0,0 +1,116 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use candle_core::{DType, Device, Tensor};
+use candle_nn::VarBuilder;
+use candle_transformers::models::bert::{BertModel, Config as BertConfig, DTYPE};
+use hf_hub::api::sync::Api;
+use hf_hub::{Repo, RepoType};
+use std::env;
+use std::fs;
+use tokenizers::{PaddingParams, Tokenizer};
+
+use crate::embedding::EmbeddingProvider;
+
+/// Runs a sentence-embedding model (default: a MiniLM-family model) entirely
+/// on-device via `candle`, with weights and tokenizer pulled through
+/// `hf-hub`'s local cache on first use. Unlike every other `EmbeddingProvider`
+/// here, `embed` never makes a network call once the model is cached, so
+/// ingestion can run with no API key and no dependency on an external
+/// embedding service being reachable.
+pub struct LocalEmbeddingProvider {
+    model: BertModel,
+    tokenizer: Tokenizer,
+    device: Device,
+    dimensions: usize,
+    model_id: String,
+}
+
+impl LocalEmbeddingProvider {
+    pub fn new() -> Result<Self> {
+        let model_id = env::var("LOCAL_EMBEDDING_MODEL")
+            .unwrap_or_else(|_| "sentence-transformers/all-MiniLM-L6-v2".to_string());
+        let revision = env::var("LOCAL_EMBEDDING_REVISION").unwrap_or_else(|_| "main".to_string());
+
+        let api = Api::new()?;
+        let repo = api.repo(Repo::with_revision(model_id.clone(), RepoType::Model, revision));
+
+        let config_path = repo.get("config.json")?;
+        let tokenizer_path = repo.get("tokenizer.json")?;
+        let weights_path = repo
+            .get("model.safetensors")
+            .or_else(|_| repo.get("pytorch_model.bin"))?;
+
+        let config: BertConfig = serde_json::from_str(&fs::read_to_string(config_path)?)?;
+        let dimensions = config.hidden_size;
+
+        let mut tokenizer = Tokenizer::from_file(tokenizer_path)
+            .map_err(|e| anyhow::anyhow!("Failed to load tokenizer for {}: {}", model_id, e))?;
+        tokenizer.with_padding(Some(PaddingParams::default()));
+
+        let device = Device::Cpu;
+        let vb = unsafe { VarBuilder::from_mmaped_safetensors(&[weights_path], DTYPE, &device)? };
+        let model = BertModel::load(vb, &config)?;
+
+        Ok(Self { model, tokenizer, device, dimensions, model_id })
+    }
+
+    /// Averages each sequence's token embeddings, zeroing out padding via the
+    /// attention mask so padded positions don't pull the mean down.
+    fn mean_pool(&self, hidden_states: &Tensor, attention_mask: &Tensor) -> Result<Tensor> {
+        let mask = attention_mask.to_dtype(DType::F32)?.unsqueeze(2)?;
+        let masked = hidden_states.broadcast_mul(&mask)?;
+        let summed = masked.sum(1)?;
+        let counts = mask.sum(1)?;
+        Ok(summed.broadcast_div(&counts)?)
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for LocalEmbeddingProvider {
+    async fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f64>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let encodings = self
+            .tokenizer
+            .encode_batch(texts, true)
+            .map_err(|e| anyhow::anyhow!("Failed to tokenize batch: {}", e))?;
+
+        let token_ids: Vec<Tensor> = encodings
+            .iter()
+            .map(|e| Tensor::new(e.get_ids(), &self.device))
+            .collect::<candle_core::Result<Vec<_>>>()?;
+        let attention_masks: Vec<Tensor> = encodings
+            .iter()
+            .map(|e| Tensor::new(e.get_attention_mask(), &self.device))
+            .collect::<candle_core::Result<Vec<_>>>()?;
+
+        let input_ids = Tensor::stack(&token_ids, 0)?;
+        let attention_mask = Tensor::stack(&attention_masks, 0)?;
+        let token_type_ids = input_ids.zeros_like()?;
+
+        // A single forward pass over the whole batch, rather than one
+        // request (and one model invocation) per chunk.
+        let hidden_states = self.model.forward(&input_ids, &token_type_ids, Some(&attention_mask))?;
+        let pooled = self.mean_pool(&hidden_states, &attention_mask)?;
+        let vectors = pooled.to_dtype(DType::F32)?.to_vec2::<f32>()?;
+
+        Ok(vectors
+            .into_iter()
+            .map(|v| v.into_iter().map(|x| x as f64).collect())
+            .collect())
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    fn max_batch(&self) -> usize {
+        32
+    }
+
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+}
@@ -1,7 +1,21 @@
 mod utils;
 mod queries;
 mod updater;
+mod events;
+mod embedding;
 mod ingestion;
+mod ingest_journal;
+mod reindex_manifest;
+mod search;
+mod sync;
+mod resilience;
+mod watch;
+mod vfs;
+mod rename;
+mod git_sync;
+mod worker;
+mod embedding_queue;
+mod local_embedding;
 
 // External crates
 use anyhow::Result;
@@ -12,30 +26,47 @@ use std::time::Instant;
 use std::io;
 use std::io::Write;
 use dotenv;
-use tokio_stream;
-use futures::StreamExt;
 use clearscreen;
 use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use indicatif::{ProgressBar, ProgressStyle};
 
 // Internal utility functions
 use utils::{
-    embed_entity_async, post_request_async, EmbeddingJob,
+    post_request_async, EmbeddingJob,
     TOTAL_CHUNKS,
     PENDING_EMBEDDINGS, COMPLETED_EMBEDDINGS,
 };
 
-use updater::update;
-use ingestion::ingestion;
+use embedding::EmbeddingProvider;
+use embedding_queue::EmbeddingQueue;
+use ingest_journal::hash_contents;
+use updater::{resolve_folder_id, update_git_aware};
+use ingestion::{ingest_shallow, ingestion};
+use worker::{WorkerHandle, WorkerState};
 
 // Remove embedding_wait_thread function entirely
 
+/// Maximum number of chunks packed into a single embedding request, and how
+/// long the batcher waits for a batch to fill before sending it anyway.
+const EMBED_BATCH_SIZE: usize = 64;
+const EMBED_BATCH_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(200);
+
+/// Number of batches embedded concurrently, separate from `EMBED_BATCH_SIZE`
+/// since each batch is already a single provider call covering many chunks.
+const MAX_CONCURRENT_EMBED_BATCHES: usize = 8;
+
 async fn async_main() {
     clear_screen();
-    let args: Vec<String> = env::args().collect();
+    let raw_args: Vec<String> = env::args().collect();
+
+    // Pull `--watch` out before positional parsing so it can appear anywhere
+    // on the command line without shifting `path`/`port`/provider indices.
+    let watch_mode = raw_args.iter().any(|a| a == "--watch");
+    let args: Vec<String> = raw_args.into_iter().filter(|a| a != "--watch").collect();
 
     let default_port = 6969;
-    
+
     // Get arguments
     let path: String = if args.len() > 1 { args[1].clone() } else { "sample".to_string() };
     let port: u16 = if args.len() > 2 { args[2].parse::<u16>().unwrap() } else { default_port };
@@ -44,71 +75,176 @@ async fn async_main() {
     println!("\nConnecting to Helix instance at port {}", port);
 
     dotenv::dotenv().ok();
-    
+
+    // Provider selection: an explicit 3rd CLI arg wins, otherwise fall back
+    // to the EMBEDDING_PROVIDER env var (embedding::provider_from_env's own
+    // "gemini" default keeps existing invocations working unchanged).
+    let provider: Arc<dyn EmbeddingProvider> = if args.len() > 3 {
+        embedding::provider_from_name(&args[3]).expect("Failed to initialize embedding provider")
+    } else {
+        embedding::provider_from_env().expect("Failed to initialize embedding provider")
+    }.into();
+    println!("Using embedding provider: {}", provider.model_id());
+
     let (tx, rx) = tokio::sync::mpsc::channel::<EmbeddingJob>(channel_buffer_size);
 
+    // The REPL's "Search" option embeds queries through the same provider,
+    // and the "Worker" option reports/controls the background task below,
+    // so keep handles to both before they're moved into the spawned task.
+    let search_provider = provider.clone();
+    let worker = WorkerHandle::new();
+    let repl_worker = worker.clone();
+
+    // Re-enqueue anything left `Pending`/`Failed` in the durable embedding
+    // queue from a prior run that crashed or was exited before finishing.
+    let embedding_queue = Arc::new(EmbeddingQueue::load_or_new());
+    let recoverable = embedding_queue.recoverable();
+    if !recoverable.is_empty() {
+        println!("Resuming {} embedding job(s) left over from a previous run", recoverable.len());
+        for (_, job) in recoverable {
+            let _ = tx.try_send(EmbeddingJob { chunk: job.chunk, entity_id: job.entity_id, port: job.port });
+        }
+    }
+
     // Spawn the async background task for embedding jobs
-    tokio::spawn(async move {
-        // Set concurrent embeddings to better utilize our rate limit
-        let max_concurrent_embeddings = 100;
-        
-        // Create a stream from the channel
-        let mut job_stream = tokio_stream::wrappers::ReceiverStream::new(rx)
-            .map(|job| {
-                async move {
-                    let EmbeddingJob { chunk, entity_id, port } = job;
-                    if !chunk.is_empty() {
-                        PENDING_EMBEDDINGS.fetch_add(1, Ordering::SeqCst);
-                        match embed_entity_async(chunk).await {
-                            Ok(embedding) => {
-                                let url = format!("http://localhost:{}/{}", port, "embedSuperEntity");
-                                let payload = json!({"entity_id": entity_id,"vector": embedding,});
-                                if let Err(e) = post_request_async(&url, payload).await {
-                                    eprintln!("Failed to post embedding: {}", e);
-                                }
-                                COMPLETED_EMBEDDINGS.fetch_add(1, Ordering::SeqCst);
-                            }
-                            Err(e) => {
-                                eprintln!("Failed to embed chunk: {}", e);
-                            }
-                        }
-                    }
-                }
-            })
-            .buffer_unordered(max_concurrent_embeddings);
-        
-        // Process the stream
-        while let Some(_) = job_stream.next().await {}
-    });
+    tokio::spawn(run_embedding_worker(rx, provider, worker, embedding_queue));
+
+    if watch_mode {
+        println!("Watch mode enabled: ingesting once, then watching {} for live changes", &path);
+    }
 
     let mut root_id = String::new();
 
     loop {
-        root_id = parse_user_input(root_id.clone(), path.clone(), port, tx.clone()).await.unwrap();
+        root_id = parse_user_input(root_id.clone(), path.clone(), port, tx.clone(), search_provider.clone(), repl_worker.clone(), watch_mode).await.unwrap();
         if root_id == "EXIT" {
             break;
         }
     }
 }
 
-async fn parse_user_input(root_id: String, path: String, port: u16, tx: tokio::sync::mpsc::Sender<EmbeddingJob>) -> Result<String> {
+/// Pulls jobs off `rx` in batches, honoring `worker`'s pause/cancel flags
+/// between batches so the REPL can pause (leaving the queue intact),
+/// resume, or cancel (draining and discarding whatever is left) the
+/// embedding pipeline at any point.
+async fn run_embedding_worker(
+    mut rx: tokio::sync::mpsc::Receiver<EmbeddingJob>,
+    provider: Arc<dyn EmbeddingProvider>,
+    worker: WorkerHandle,
+    queue: Arc<EmbeddingQueue>,
+) {
+    let semaphore = Arc::new(tokio::sync::Semaphore::new(MAX_CONCURRENT_EMBED_BATCHES));
+
+    loop {
+        if worker.is_cancelled() {
+            let mut abandoned = 0;
+            while rx.try_recv().is_ok() {
+                abandoned += 1;
+            }
+            worker.record_abandoned(abandoned);
+            break;
+        }
+
+        if worker.is_paused() {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            continue;
+        }
+
+        match collect_batch(&mut rx, EMBED_BATCH_SIZE.min(provider.max_batch()), EMBED_BATCH_TIMEOUT).await {
+            None => break,
+            Some(batch) if batch.is_empty() => continue,
+            Some(batch) => {
+                let permit = match semaphore.clone().acquire_owned().await {
+                    Ok(permit) => permit,
+                    Err(_) => break,
+                };
+                let provider = provider.clone();
+                let queue = queue.clone();
+                tokio::spawn(async move {
+                    embed_batch(batch, provider, queue).await;
+                    drop(permit);
+                });
+            }
+        }
+    }
+
+    worker.mark_dead();
+}
+
+/// Accumulates up to `max` jobs from `rx`, skipping empty chunks, flushing
+/// early once `window` has elapsed since the first job in the batch arrived.
+/// Returns an empty batch if no job arrives within `window` (so the caller
+/// can check for pause/cancel while the channel is idle), and `None` once the
+/// channel has closed and has nothing left to offer.
+async fn collect_batch(
+    rx: &mut tokio::sync::mpsc::Receiver<EmbeddingJob>,
+    max: usize,
+    window: std::time::Duration,
+) -> Option<Vec<EmbeddingJob>> {
+    let mut batch = Vec::with_capacity(max);
+
+    match tokio::time::timeout(window, rx.recv()).await {
+        Ok(Some(job)) => {
+            if !job.chunk.is_empty() {
+                batch.push(job);
+            }
+        }
+        Ok(None) => return None,
+        Err(_) => return Some(batch),
+    }
+
+    let deadline = Instant::now() + window;
+    while batch.len() < max {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        match tokio::time::timeout(remaining, rx.recv()).await {
+            Ok(Some(job)) => {
+                if !job.chunk.is_empty() {
+                    batch.push(job);
+                }
+            }
+            Ok(None) | Err(_) => break,
+        }
+    }
+
+    Some(batch)
+}
+
+async fn parse_user_input(
+    root_id: String,
+    path: String,
+    port: u16,
+    tx: tokio::sync::mpsc::Sender<EmbeddingJob>,
+    provider: Arc<dyn EmbeddingProvider>,
+    worker: WorkerHandle,
+    watch_mode: bool,
+) -> Result<String> {
     let path_buf = PathBuf::from(path.clone());
     let root_name = path_buf.file_name().unwrap().to_str().unwrap();
     println!("\nWhat would you like to do?\n");
     println!("1 : Ingest {}", &root_name);
     println!("2 : Update {}", &root_name);
     println!("3 : Exit");
-    
+    println!("4 : Search {}", &root_name);
+    println!("5 : Embedding worker status / pause / resume / cancel");
+    println!("6 : Reindex a subtree of {} (no recursion into subfolders)", &root_name);
+
     io::stdout().flush().unwrap();
     let mut input = String::new();
     io::stdin().read_line(&mut input).unwrap();
     let input = input.trim().to_string();
     let start_time = Instant::now();
     if input == "1" {
+        let (events_tx, events_rx) = tokio::sync::mpsc::channel(1000);
+        events::spawn_console_consumer(events_rx);
         let root_id = ingestion(
             path_buf.canonicalize().expect("Failed to canonicalize path"),
             port,
             tx.clone(),
+            ingestion::DEFAULT_INGEST_CONCURRENCY,
+            Some(events_tx),
         ).await;
 
         clear_screen();
@@ -118,14 +254,21 @@ async fn parse_user_input(root_id: String, path: String, port: u16, tx: tokio::s
         wait_for_embeddings(start_time).await;
         TOTAL_CHUNKS.store(0, Ordering::SeqCst);
 
-        return Ok(root_id.unwrap().to_string());
+        let root_id = root_id.unwrap().to_string();
+        if watch_mode {
+            if let Err(e) = start_watch(path_buf.canonicalize().expect("Failed to canonicalize path"), root_id.clone(), port, tx.clone()).await {
+                eprintln!("Failed to start watch mode: {}", e);
+            }
+        }
+
+        return Ok(root_id);
     } else if input == "2" {
         clear_screen();
         let root_ids = get_root_ids(port).await?;
         if root_ids.contains(&root_id) {
             println!("\nUpdating index...");
-            let _ = update(
-                path_buf.canonicalize().unwrap(), root_id.clone(), 
+            let _ = update_git_aware(
+                path_buf.canonicalize().unwrap(), root_id.clone(),
                 port, tx.clone(), 5
             ).await;
             println!("\nUpdate finished in {} seconds", start_time.elapsed().as_secs());
@@ -139,6 +282,101 @@ async fn parse_user_input(root_id: String, path: String, port: u16, tx: tokio::s
     } else if input == "3" {
         clear_screen();
         return Ok("EXIT".to_string());
+    } else if input == "4" {
+        clear_screen();
+        print!("Search query: ");
+        io::stdout().flush().unwrap();
+        let mut query = String::new();
+        io::stdin().read_line(&mut query).unwrap();
+        let query = query.trim().to_string();
+
+        if query.is_empty() {
+            println!("Empty query");
+            return Ok(root_id);
+        }
+
+        match search::search_code(query, 10, port, provider.as_ref()).await {
+            Ok(hits) => {
+                if hits.is_empty() {
+                    println!("No matches found");
+                } else {
+                    for (rank, hit) in hits.iter().enumerate() {
+                        println!(
+                            "{}. {} [{}..{}] (score {:.4})",
+                            rank + 1,
+                            hit.file_name.as_deref().unwrap_or("<unknown file>"),
+                            hit.start_byte,
+                            hit.end_byte,
+                            hit.score,
+                        );
+                        println!("   {}", hit.text.lines().next().unwrap_or("").trim());
+                    }
+                }
+            }
+            Err(e) => eprintln!("Search failed: {}", e),
+        }
+
+        return Ok(root_id);
+    } else if input == "5" {
+        clear_screen();
+        let (pending, completed) = worker.counts();
+        println!("Worker state: {:?}", worker.state());
+        println!("Pending: {}  Completed: {}", pending, completed);
+        if worker.state() == WorkerState::Dead {
+            println!("Abandoned on cancel: {}", worker.abandoned_count());
+        }
+        println!("\np : Pause   r : Resume   c : Cancel (discard queued jobs)   (anything else: back)");
+
+        io::stdout().flush().unwrap();
+        let mut sub_input = String::new();
+        io::stdin().read_line(&mut sub_input).unwrap();
+        match sub_input.trim() {
+            "p" => {
+                worker.pause();
+                println!("Worker paused");
+            }
+            "r" => {
+                worker.resume();
+                println!("Worker resumed");
+            }
+            "c" => {
+                worker.cancel();
+                println!("Cancelling worker; queued jobs will be discarded");
+            }
+            _ => {}
+        }
+
+        return Ok(root_id);
+    } else if input == "6" {
+        clear_screen();
+        print!("Subtree path (relative to {}, blank for the root itself): ", &root_name);
+        io::stdout().flush().unwrap();
+        let mut subpath = String::new();
+        io::stdin().read_line(&mut subpath).unwrap();
+        let subpath = subpath.trim();
+
+        let root_path = path_buf.canonicalize().expect("Failed to canonicalize path");
+        let target_path = if subpath.is_empty() { root_path.clone() } else { root_path.join(subpath) };
+
+        let folder_id = if subpath.is_empty() {
+            Some(root_id.clone())
+        } else {
+            resolve_folder_id(&root_path, &root_id, &target_path, port).await?
+        };
+
+        match folder_id {
+            Some(folder_id) => {
+                println!("\nReindexing {}...", target_path.display());
+                if let Err(e) = ingest_shallow(target_path, folder_id, port, tx.clone()).await {
+                    eprintln!("Reindex failed: {}", e);
+                }
+                wait_for_embeddings(start_time).await;
+                TOTAL_CHUNKS.store(0, Ordering::SeqCst);
+            }
+            None => println!("\n{} isn't indexed yet", subpath),
+        }
+
+        return Ok(root_id);
     }
 
     clear_screen();
@@ -146,6 +384,99 @@ async fn parse_user_input(root_id: String, path: String, port: u16, tx: tokio::s
     return Ok(root_id);
 }
 
+/// Embeds a whole batch of jobs in one provider call instead of one call per
+/// chunk (the OpenAI, Ollama and self-hosted providers send it as one HTTP
+/// request; `GeminiProvider` still sends one request per chunk). If the
+/// batch fails outright, or the provider comes
+/// back with the wrong number of vectors, falls back to retrying each job
+/// individually so one bad chunk doesn't drop the rest of the batch.
+///
+/// Before dispatching, skips any chunk whose content hash is already
+/// recorded `Done` in `queue` (a duplicate within this run, or one a prior
+/// run finished but crashed before exiting cleanly) and records the rest as
+/// `Pending` so a crash mid-batch leaves them resumable instead of lost.
+async fn embed_batch(jobs: Vec<EmbeddingJob>, provider: Arc<dyn EmbeddingProvider>, queue: Arc<EmbeddingQueue>) {
+    if jobs.is_empty() {
+        return;
+    }
+
+    PENDING_EMBEDDINGS.fetch_add(jobs.len(), Ordering::SeqCst);
+
+    let mut pending_jobs = Vec::with_capacity(jobs.len());
+    for job in jobs {
+        let hash = hash_contents(job.chunk.as_bytes());
+        if queue.is_done(hash) {
+            COMPLETED_EMBEDDINGS.fetch_add(1, Ordering::SeqCst);
+            continue;
+        }
+        queue.record_pending(hash, job.entity_id.clone(), job.chunk.clone(), job.port);
+        pending_jobs.push((hash, job));
+    }
+
+    if pending_jobs.is_empty() {
+        return;
+    }
+
+    let chunks: Vec<String> = pending_jobs.iter().map(|(_, job)| job.chunk.clone()).collect();
+    match provider.embed(chunks).await {
+        Ok(embeddings) if embeddings.len() == pending_jobs.len() => {
+            for ((hash, job), embedding) in pending_jobs.into_iter().zip(embeddings.into_iter()) {
+                post_embedding(hash, job, Ok(embedding), &queue).await;
+            }
+        }
+        Ok(embeddings) => {
+            eprintln!(
+                "Embedding batch returned {} vectors for {} chunks; retrying members individually",
+                embeddings.len(),
+                pending_jobs.len(),
+            );
+            retry_batch_individually(pending_jobs, provider, queue).await;
+        }
+        Err(e) => {
+            eprintln!("Failed to embed batch of {} chunks: {}; retrying members individually", pending_jobs.len(), e);
+            retry_batch_individually(pending_jobs, provider, queue).await;
+        }
+    }
+}
+
+/// Re-embeds each job in `jobs` one at a time, used when a batched call
+/// fails so the other members of the batch aren't dropped along with it.
+async fn retry_batch_individually(jobs: Vec<(u64, EmbeddingJob)>, provider: Arc<dyn EmbeddingProvider>, queue: Arc<EmbeddingQueue>) {
+    for (hash, job) in jobs {
+        let chunk = job.chunk.clone();
+        let result = provider.embed(vec![chunk]).await.and_then(|mut vectors| {
+            vectors.pop().ok_or_else(|| anyhow::anyhow!("embedding provider returned no vectors"))
+        });
+        post_embedding(hash, job, result, &queue).await;
+    }
+}
+
+/// Posts one job's resulting embedding (or logs the failure) and keeps
+/// `COMPLETED_EMBEDDINGS` accurate per-item regardless of how it was batched.
+/// Marks the queue entry `Done` only once the POST itself succeeds; any
+/// other outcome leaves it `Failed` so the next startup retries it.
+async fn post_embedding(hash: u64, job: EmbeddingJob, result: Result<Vec<f64>>, queue: &EmbeddingQueue) {
+    let EmbeddingJob { entity_id, port, .. } = job;
+    match result {
+        Ok(embedding) => {
+            let url = format!("http://localhost:{}/{}", port, "embedSuperEntity");
+            let payload = json!({"entity_id": entity_id,"vector": embedding,});
+            match post_request_async(&url, payload).await {
+                Ok(_) => queue.mark_done(hash),
+                Err(e) => {
+                    eprintln!("Failed to post embedding: {}", e);
+                    queue.mark_failed(hash);
+                }
+            }
+            COMPLETED_EMBEDDINGS.fetch_add(1, Ordering::SeqCst);
+        }
+        Err(e) => {
+            eprintln!("Failed to embed chunk: {}", e);
+            queue.mark_failed(hash);
+        }
+    }
+}
+
 async fn wait_for_embeddings(start_time: Instant) {
     use tokio::time::{sleep, Duration};
     println!("Waiting for all embedding jobs to complete...");
@@ -175,6 +506,32 @@ async fn wait_for_embeddings(start_time: Instant) {
     COMPLETED_EMBEDDINGS.store(0, Ordering::SeqCst);
 }
 
+/// Loads the same `index-types.json`/`file_types.json` config `ingestion`
+/// reads, then hands `watch::watch_root` off to a background task so the
+/// REPL stays usable while the root is watched for live changes. Runs for
+/// the remainder of the process; the only way to stop it today is to exit.
+async fn start_watch(
+    root_path: PathBuf,
+    root_id: String,
+    port: u16,
+    tx: tokio::sync::mpsc::Sender<EmbeddingJob>,
+) -> Result<()> {
+    let index_types = std::fs::read_to_string("index-types.json")?;
+    let index_types: Arc<serde_json::Value> = Arc::new(serde_json::from_str(&index_types)?);
+
+    let file_types = std::fs::read_to_string("file_types.json")?;
+    let file_types: Arc<serde_json::Value> = Arc::new(serde_json::from_str(&file_types)?);
+
+    println!("\nWatching {} for changes...", root_path.display());
+    tokio::spawn(async move {
+        if let Err(e) = watch::watch_root(root_path, root_id, port, index_types, file_types, tx).await {
+            eprintln!("Watch mode stopped: {}", e);
+        }
+    });
+
+    Ok(())
+}
+
 fn clear_screen() {
     clearscreen::clear().expect("Failed to clear screen");
 }
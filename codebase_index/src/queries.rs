@@ -73,6 +73,31 @@ pub async fn get_sub_folders (
     Ok(subfolder_name_ids)    
 }
 
+/// Fetches the commit SHA the server recorded as fully indexed for this
+/// root, or `None` if it has never been set (first run, or a non-Git root).
+pub async fn get_root_commit_sha(
+    root_id: String,
+    port: u16
+) -> Result<Option<String>> {
+    let url = format!("http://localhost:{}/{}", port, "getRootCommitSha");
+    let payload = json!({ "root_id": root_id });
+    let res = post_request_async(&url, payload).await?;
+    Ok(res.get("commit_sha").and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(|s| s.to_string()))
+}
+
+/// Looks up the content hash the server stored for `file_id` on its last
+/// ingest/rename, used to match renamed/moved files by content instead of
+/// name. `None` if the server has no hash on record for this file yet.
+pub async fn get_file_hash(
+    file_id: String,
+    port: u16
+) -> Result<Option<String>> {
+    let url = format!("http://localhost:{}/{}", port, "getFileHash");
+    let payload = json!({ "file_id": file_id });
+    let res = post_request_async(&url, payload).await?;
+    Ok(res.get("hash").and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(|s| s.to_string()))
+}
+
 pub async fn get_folder_files (
     folder_id: String,
     port: u16
@@ -0,0 +1,143 @@
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// What `process_file` recorded for one top-level entity the last time it
+/// ingested the file, keyed by byte range so an unchanged entity can be
+/// left alone when the file around it changed.
+#[derive(Debug, Clone)]
+pub struct EntityManifestEntry {
+    pub start_byte: usize,
+    pub end_byte: usize,
+    pub hash: String,
+}
+
+/// What `process_file` recorded for one file the last time it ingested it.
+#[derive(Debug, Clone)]
+pub struct FileManifestEntry {
+    pub hash: String,
+    pub file_id: String,
+    pub entities: Vec<EntityManifestEntry>,
+}
+
+/// Sidecar cache mapping each ingested path to its last-seen content hash
+/// and server-side file id, persisted between runs so a warm re-ingestion
+/// can skip files whose content hasn't changed, and `updateFile` in place
+/// of `createFile` for the ones that have, instead of re-walking the whole
+/// repo through the embedding pipeline every time.
+#[derive(Debug, Default)]
+pub struct ReindexManifest {
+    root_path: PathBuf,
+    entries: HashMap<PathBuf, FileManifestEntry>,
+}
+
+impl ReindexManifest {
+    fn manifest_path_for(root_path: &Path) -> PathBuf {
+        let root_name = root_path.file_name().and_then(|s| s.to_str()).unwrap_or("root");
+        PathBuf::from(".medkit-jobs").join(format!("{}.manifest.json", root_name))
+    }
+
+    /// Loads the manifest for `root_path` if one exists on disk, otherwise
+    /// starts an empty one.
+    pub fn load_or_new(root_path: PathBuf) -> Self {
+        let manifest_path = Self::manifest_path_for(&root_path);
+        let mut entries = HashMap::new();
+        if let Ok(contents) = fs::read_to_string(&manifest_path) {
+            if let Ok(parsed) = serde_json::from_str::<Value>(&contents) {
+                if let Some(files) = parsed.get("files").and_then(|v| v.as_array()) {
+                    for file in files {
+                        let path = match file.get("path").and_then(|v| v.as_str()) {
+                            Some(path) => path,
+                            None => continue,
+                        };
+                        let hash = match file.get("hash").and_then(|v| v.as_str()) {
+                            Some(hash) => hash,
+                            None => continue,
+                        };
+                        let file_id = match file.get("file_id").and_then(|v| v.as_str()) {
+                            Some(file_id) => file_id,
+                            None => continue,
+                        };
+                        let entities = file
+                            .get("entities")
+                            .and_then(|v| v.as_array())
+                            .map(|array| {
+                                array
+                                    .iter()
+                                    .filter_map(|entity| {
+                                        Some(EntityManifestEntry {
+                                            start_byte: entity.get("start_byte")?.as_u64()? as usize,
+                                            end_byte: entity.get("end_byte")?.as_u64()? as usize,
+                                            hash: entity.get("hash")?.as_str()?.to_string(),
+                                        })
+                                    })
+                                    .collect()
+                            })
+                            .unwrap_or_default();
+                        entries.insert(
+                            PathBuf::from(path),
+                            FileManifestEntry { hash: hash.to_string(), file_id: file_id.to_string(), entities },
+                        );
+                    }
+                }
+            }
+        }
+        ReindexManifest { root_path, entries }
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&FileManifestEntry> {
+        self.entries.get(path)
+    }
+
+    pub fn record(&mut self, path: PathBuf, hash: String, file_id: String, entities: Vec<EntityManifestEntry>) {
+        self.entries.insert(path, FileManifestEntry { hash, file_id, entities });
+    }
+
+    /// Every path this manifest still remembers, so a caller can check each
+    /// one against the filesystem and reconcile whatever has disappeared.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.entries.keys().cloned().collect()
+    }
+
+    /// Drops `path` from the manifest, e.g. once its server-side file and
+    /// entities have been deleted because the file is gone from disk.
+    pub fn remove(&mut self, path: &Path) {
+        self.entries.remove(path);
+    }
+
+    /// Writes the manifest back out as JSON, overwriting the previous copy.
+    pub fn persist(&self) -> Result<()> {
+        let manifest_path = Self::manifest_path_for(&self.root_path);
+        if let Some(parent) = manifest_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let files: Vec<Value> = self
+            .entries
+            .iter()
+            .map(|(path, entry)| {
+                let entities: Vec<Value> = entry
+                    .entities
+                    .iter()
+                    .map(|e| json!({ "start_byte": e.start_byte, "end_byte": e.end_byte, "hash": e.hash }))
+                    .collect();
+                json!({
+                    "path": path.to_string_lossy(),
+                    "hash": entry.hash,
+                    "file_id": entry.file_id,
+                    "entities": entities,
+                })
+            })
+            .collect();
+        let manifest = json!({ "files": files });
+        fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+        Ok(())
+    }
+}
+
+/// Fast content hash used to detect whether a file (or entity range) changed
+/// since the last ingestion run.
+pub fn hash_text(text: &str) -> String {
+    blake3::hash(text.as_bytes()).to_hex().to_string()
+}
@@ -0,0 +1,92 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::queries::get_file_hash;
+use crate::sync::hash_file;
+use crate::utils::rename_file;
+
+/// An unseen (possibly deleted) indexed file matched up with a newly seen
+/// path that turned out to have identical content.
+pub struct RenameMatch {
+    pub old_name: String,
+    pub new_path: PathBuf,
+}
+
+/// Matches `unseen_files` (present in the index, missing from the current
+/// walk) against `new_paths` (present in the walk, missing from the index)
+/// by content hash, so a plain rename/move doesn't trigger a delete followed
+/// by a full re-ingest. Empty files are excluded from matching since an
+/// empty-file hash collision carries no useful signal; they fall back to
+/// the normal delete-then-create path.
+///
+/// Returns the matches (already applied server-side via `rename_file`),
+/// plus the unseen names and new paths that weren't matched and should go
+/// through the usual delete/create handling.
+pub async fn detect_renames(
+    unseen_files: Vec<String>,
+    file_name_ids: &HashMap<String, (String, String)>,
+    new_paths: Vec<PathBuf>,
+    port: u16,
+) -> Result<(Vec<RenameMatch>, Vec<String>, Vec<PathBuf>)> {
+    if unseen_files.is_empty() || new_paths.is_empty() {
+        return Ok((Vec::new(), unseen_files, new_paths));
+    }
+
+    // hash -> candidate old names, sorted so matching is deterministic when
+    // several unseen files share identical content.
+    let mut by_hash: HashMap<String, Vec<String>> = HashMap::new();
+    for file_name in &unseen_files {
+        let file_id = match file_name_ids.get(file_name) {
+            Some((id, _)) => id.clone(),
+            None => continue,
+        };
+        if let Some(hash) = get_file_hash(file_id, port).await? {
+            by_hash.entry(hash).or_default().push(file_name.clone());
+        }
+    }
+    for candidates in by_hash.values_mut() {
+        candidates.sort();
+    }
+
+    let mut matches = Vec::new();
+    let mut matched_old: std::collections::HashSet<String> = std::collections::HashSet::new();
+    let mut remaining_new = Vec::new();
+
+    let mut new_paths = new_paths;
+    new_paths.sort();
+
+    for new_path in new_paths {
+        let size_is_zero = std::fs::metadata(&new_path).map(|m| m.len() == 0).unwrap_or(true);
+        if size_is_zero {
+            remaining_new.push(new_path);
+            continue;
+        }
+
+        let hash = hash_file(&new_path)?;
+        let candidate = by_hash
+            .get_mut(&hash)
+            .and_then(|candidates| {
+                let idx = candidates.iter().position(|c| !matched_old.contains(c))?;
+                Some(candidates.remove(idx))
+            });
+
+        match candidate {
+            Some(old_name) => {
+                let (file_id, _) = file_name_ids.get(&old_name).unwrap().clone();
+                let new_name = new_path.file_name().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+                rename_file(file_id, new_name, hash, port).await?;
+                matched_old.insert(old_name.clone());
+                matches.push(RenameMatch { old_name, new_path });
+            }
+            None => remaining_new.push(new_path),
+        }
+    }
+
+    let remaining_unseen = unseen_files
+        .into_iter()
+        .filter(|name| !matched_old.contains(name))
+        .collect();
+
+    Ok((matches, remaining_unseen, remaining_new))
+}
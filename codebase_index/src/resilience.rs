@@ -0,0 +1,180 @@
+use anyhow::Result;
+use rand::Rng;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Exponential backoff with jitter, shared by both the HelixDB and
+/// embedding HTTP call sites.
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Computes the delay before attempt `attempt` (0-indexed), honoring a
+    /// server-provided `Retry-After` header when present.
+    pub fn delay_for(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        if let Some(retry_after) = retry_after {
+            return retry_after.min(self.max_delay);
+        }
+
+        let exp = self.base_delay.as_millis().saturating_mul(1u128 << attempt.min(20));
+        let capped = exp.min(self.max_delay.as_millis());
+        let jitter = rand::thread_rng().gen_range(0..=(capped / 2).max(1));
+        Duration::from_millis((capped / 2 + jitter) as u64)
+    }
+}
+
+/// Classifies whether a response/error should be retried. Non-retryable 4xx
+/// responses fail fast rather than burning through attempts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    Retryable,
+    RateLimited,
+    NonRetryable,
+}
+
+pub fn classify_status(status: u16) -> FailureKind {
+    match status {
+        429 => FailureKind::RateLimited,
+        500..=599 => FailureKind::Retryable,
+        _ => FailureKind::NonRetryable,
+    }
+}
+
+/// Per-endpoint circuit breaker: after `failure_threshold` consecutive
+/// failures, short-circuits new requests for `cooldown` so a struggling
+/// HelixDB/Gemini instance isn't hammered while it recovers.
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    consecutive_failures: AtomicU32,
+    opened_at: Mutex<Option<Instant>>,
+    trips: AtomicU64,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold,
+            cooldown,
+            consecutive_failures: AtomicU32::new(0),
+            opened_at: Mutex::new(None),
+            trips: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns `true` if requests should currently be short-circuited.
+    pub fn is_open(&self) -> bool {
+        let mut opened_at = self.opened_at.lock().unwrap();
+        match *opened_at {
+            Some(at) if at.elapsed() < self.cooldown => true,
+            Some(_) => {
+                // Cooldown elapsed: allow a trial request through.
+                *opened_at = None;
+                false
+            }
+            None => false,
+        }
+    }
+
+    pub fn record_success(&self) {
+        self.consecutive_failures.store(0, Ordering::SeqCst);
+        *self.opened_at.lock().unwrap() = None;
+    }
+
+    pub fn record_failure(&self) {
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::SeqCst) + 1;
+        if failures >= self.failure_threshold {
+            let mut opened_at = self.opened_at.lock().unwrap();
+            if opened_at.is_none() {
+                *opened_at = Some(Instant::now());
+                self.trips.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }
+}
+
+/// Registry of circuit breakers keyed by endpoint so each HelixDB/Gemini
+/// route trips independently instead of sharing one global breaker.
+pub struct CircuitBreakerRegistry {
+    breakers: Mutex<HashMap<String, std::sync::Arc<CircuitBreaker>>>,
+    failure_threshold: u32,
+    cooldown: Duration,
+}
+
+impl CircuitBreakerRegistry {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            breakers: Mutex::new(HashMap::new()),
+            failure_threshold,
+            cooldown,
+        }
+    }
+
+    pub fn get(&self, endpoint: &str) -> std::sync::Arc<CircuitBreaker> {
+        let mut breakers = self.breakers.lock().unwrap();
+        breakers
+            .entry(endpoint.to_string())
+            .or_insert_with(|| std::sync::Arc::new(CircuitBreaker::new(self.failure_threshold, self.cooldown)))
+            .clone()
+    }
+}
+
+/// Retries `op` according to `policy`, tripping/clearing `breaker` on
+/// failure/success. `op` returns `Ok` on success, or `Err((kind, retry_after))`
+/// describing how the failure should be treated.
+pub async fn retry_with_backoff<T, F, Fut>(
+    endpoint: &str,
+    registry: &CircuitBreakerRegistry,
+    policy: RetryPolicy,
+    mut op: F,
+) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, (FailureKind, Option<Duration>, anyhow::Error)>>,
+{
+    let breaker = registry.get(endpoint);
+
+    if breaker.is_open() {
+        return Err(anyhow::anyhow!("Circuit breaker open for endpoint: {}", endpoint));
+    }
+
+    let mut last_error = None;
+    for attempt in 0..policy.max_attempts {
+        match op().await {
+            Ok(value) => {
+                breaker.record_success();
+                return Ok(value);
+            }
+            Err((FailureKind::NonRetryable, _, err)) => {
+                breaker.record_failure();
+                return Err(err);
+            }
+            Err((kind, retry_after, err)) => {
+                breaker.record_failure();
+                last_error = Some(err);
+                if attempt + 1 < policy.max_attempts {
+                    let delay = policy.delay_for(attempt, if kind == FailureKind::RateLimited { retry_after } else { None });
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| anyhow::anyhow!("Retry attempts exhausted for endpoint: {}", endpoint)))
+}
@@ -0,0 +1,187 @@
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+use crate::embedding::EmbeddingProvider;
+use crate::queries::{get_folder_files, get_root_files};
+use crate::utils::post_request_async;
+
+/// A single ranked hit returned from `search_code`.
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub entity_id: String,
+    pub entity_type: String,
+    pub text: String,
+    pub start_byte: usize,
+    pub end_byte: usize,
+    pub score: f64,
+    pub file_name: Option<String>,
+}
+
+/// Embeds `query` through `provider` (the same one ingestion used to produce
+/// the stored vectors) and returns the top-`k` most similar entities in the
+/// HelixDB vector store, re-scored against the raw query for precision.
+pub async fn search_code(query: String, k: usize, port: u16, provider: &dyn EmbeddingProvider) -> Result<Vec<SearchHit>> {
+    let query_vector = embed_query(query.clone(), provider).await?;
+
+    // Over-fetch so the rerank pass has room to improve precision.
+    let rerank_pool = k.saturating_mul(4).max(k);
+
+    let url = format!("http://localhost:{}/{}", port, "vectorSearch");
+    let payload = json!({ "vector": query_vector, "k": rerank_pool });
+    let response = post_request_async(&url, payload).await?;
+
+    let candidates = response
+        .get("entity")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| anyhow::anyhow!("No entities returned from vectorSearch"))?;
+
+    let mut hits = Vec::with_capacity(candidates.len());
+    for candidate in candidates {
+        let entity_id = candidate
+            .get("id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Entity ID not found"))?
+            .to_string();
+        let entity_type = candidate
+            .get("entity_type")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let text = candidate
+            .get("text")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let start_byte = candidate.get("start_byte").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+        let end_byte = candidate.get("end_byte").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+        let score = candidate.get("score").and_then(|v| v.as_f64()).unwrap_or(0.0);
+
+        hits.push(SearchHit {
+            entity_id,
+            entity_type,
+            text,
+            start_byte,
+            end_byte,
+            score,
+            file_name: None,
+        });
+    }
+
+    rerank(&mut hits, &query);
+    hits.truncate(k);
+
+    resolve_file_names(&mut hits, port).await?;
+
+    Ok(hits)
+}
+
+/// Embeds the search query through the same provider used to index the
+/// stored chunks, so query and document vectors live in the same space.
+async fn embed_query(query: String, provider: &dyn EmbeddingProvider) -> Result<Vec<f64>> {
+    provider.embed(vec![query]).await?
+        .pop()
+        .ok_or_else(|| anyhow::anyhow!("embedding provider returned no vectors for the query"))
+}
+
+/// Re-scores the candidate pool against the raw query text using a cheap
+/// lexical overlap signal, nudging exact-match candidates above purely
+/// semantic neighbors without a second embedding round-trip.
+fn rerank(hits: &mut [SearchHit], query: &str) {
+    let query_terms: Vec<String> = query
+        .split_whitespace()
+        .map(|t| t.to_lowercase())
+        .collect();
+
+    for hit in hits.iter_mut() {
+        if query_terms.is_empty() {
+            continue;
+        }
+        let text_lower = hit.text.to_lowercase();
+        let overlap = query_terms
+            .iter()
+            .filter(|term| text_lower.contains(term.as_str()))
+            .count() as f64
+            / query_terms.len() as f64;
+        hit.score = hit.score * 0.85 + overlap * 0.15;
+    }
+
+    hits.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+}
+
+/// Resolves each hit's owning file name via the root or folder it belongs to,
+/// mirroring the name maps already used by the updater for reconciliation.
+async fn resolve_file_names(hits: &mut [SearchHit], port: u16) -> Result<()> {
+    let mut file_id_to_name: HashMap<String, String> = HashMap::new();
+
+    for hit in hits.iter_mut() {
+        let url = format!("http://localhost:{}/{}", port, "getEntityFile");
+        let response: Result<Value> =
+            post_request_async(&url, json!({ "entity_id": hit.entity_id })).await;
+
+        let file_id = match response {
+            Ok(res) => res
+                .get("file")
+                .and_then(|v| v.as_array())
+                .and_then(|arr| arr.first())
+                .and_then(|v| v.get("id"))
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            Err(_) => None,
+        };
+
+        if let Some(file_id) = file_id {
+            if let Some(name) = file_id_to_name.get(&file_id) {
+                hit.file_name = Some(name.clone());
+                continue;
+            }
+
+            let url = format!("http://localhost:{}/{}", port, "getFileRoot");
+            if let Ok(root_res) = post_request_async(&url, json!({ "file_id": file_id })).await {
+                if let Some(root_id) = root_res
+                    .get("root")
+                    .and_then(|v| v.as_array())
+                    .and_then(|arr| arr.first())
+                    .and_then(|v| v.get("id"))
+                    .and_then(|v| v.as_str())
+                {
+                    if let Ok(root_files) = get_root_files(root_id.to_string(), port).await {
+                        if let Some((name, _)) = root_files
+                            .iter()
+                            .find(|(_, (id, _))| *id == file_id)
+                            .map(|(name, val)| (name.clone(), val.clone()))
+                        {
+                            file_id_to_name.insert(file_id.clone(), name.clone());
+                            hit.file_name = Some(name);
+                            continue;
+                        }
+                    }
+                }
+            }
+
+            let url = format!("http://localhost:{}/{}", port, "getFileFolder");
+            if let Ok(folder_res) = post_request_async(&url, json!({ "file_id": file_id })).await {
+                if let Some(folder_id) = folder_res
+                    .get("folder")
+                    .and_then(|v| v.as_array())
+                    .and_then(|arr| arr.first())
+                    .and_then(|v| v.get("id"))
+                    .and_then(|v| v.as_str())
+                {
+                    if let Ok(folder_files) = get_folder_files(folder_id.to_string(), port).await {
+                        if let Some((name, _)) = folder_files
+                            .iter()
+                            .find(|(_, (id, _))| *id == file_id)
+                            .map(|(name, val)| (name.clone(), val.clone()))
+                        {
+                            file_id_to_name.insert(file_id.clone(), name.clone());
+                            hit.file_name = Some(name);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
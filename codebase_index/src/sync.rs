@@ -0,0 +1,11 @@
+use anyhow::Result;
+use std::fs;
+use std::path::PathBuf;
+
+/// Computes a blake3 content hash of a file's bytes. Used by `rename.rs` to
+/// match a disappeared file against a newly-appeared one by content instead
+/// of name.
+pub fn hash_file(path: &PathBuf) -> Result<String> {
+    let bytes = fs::read(path)?;
+    Ok(blake3::hash(&bytes).to_hex().to_string())
+}
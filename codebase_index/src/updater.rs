@@ -1,8 +1,10 @@
 use anyhow::Result;
+use futures::stream::{self, StreamExt};
 use ignore::WalkBuilder;
 use serde_json::json;
+use std::collections::HashSet;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{
     atomic::{AtomicUsize, Ordering},
     Arc
@@ -12,19 +14,67 @@ use tokio::task::JoinHandle;
 use chrono::{DateTime, Utc};
 use async_recursion::async_recursion;
 
+/// Caps how many entries `update`/`update_folder` reconcile at once so a
+/// large directory doesn't open thousands of concurrent HTTP connections to
+/// the local server.
+const DEFAULT_WALK_CONCURRENCY: usize = 32;
+
 
 // Import from our modules
 use crate::utils::{
-    post_request_async, delete_folder, delete_files, EmbeddingJob,
+    post_request_async, delete_folder, delete_files, set_root_commit_sha, EmbeddingJob,
     TOTAL_CHUNKS
 };
-use crate::queries::{get_root_folders, get_root_files, get_sub_folders, get_folder_files};
+use crate::queries::{get_root_folders, get_root_files, get_sub_folders, get_folder_files, get_root_commit_sha};
 
 // Forward declarations for functions that will be moved from ingestion
 use crate::ingestion::{populate, process_file, ingest_entities, process_unsupported_file, build_owned_nodes};
 use crate::utils::{get_language, delete_entities, chunk_entity};
+use crate::vfs::{Fs, OsFs};
 use tree_sitter::Parser;
 
+/// What an already-indexed file needs, decided purely from its on-disk
+/// modified time (or lack thereof) vs. the timestamp recorded at last
+/// extraction. Kept free of any HTTP calls so it can be table-tested
+/// against a `FakeFs`.
+#[derive(Debug, PartialEq)]
+pub(crate) enum FileReconcileAction {
+    UpToDate,
+    OutOfDate,
+    ModifiedTimeUnavailable,
+}
+
+pub(crate) async fn reconcile_file_action(
+    fs: &dyn Fs,
+    path: &Path,
+    extracted_at: &str,
+    update_interval: u64,
+) -> FileReconcileAction {
+    match fs.modified(path).await {
+        Ok(last_modified) => {
+            let date_modified = DateTime::<Utc>::from(last_modified);
+            let date_extracted = DateTime::parse_from_rfc3339(extracted_at)
+                .expect("Failed to parse date")
+                .with_timezone(&Utc);
+            let diff_sec = date_modified.signed_duration_since(date_extracted).num_seconds();
+            if diff_sec > update_interval.try_into().unwrap() {
+                FileReconcileAction::OutOfDate
+            } else {
+                FileReconcileAction::UpToDate
+            }
+        }
+        Err(_) => FileReconcileAction::ModifiedTimeUnavailable,
+    }
+}
+
+/// Names present in `known` but absent from `seen` - the shared "vanished
+/// from the walk" check behind both unseen-folder and unseen-file-precheck
+/// detection in `update`/`update_folder`. Pure set logic, no I/O involved,
+/// so it's trivially table-tested.
+pub(crate) fn unseen_entries<'a>(known: impl Iterator<Item = &'a String>, seen: &HashSet<String>) -> Vec<String> {
+    known.filter(|name| !seen.contains(*name)).cloned().collect()
+}
+
 #[async_recursion]
 pub async fn update(
     root_path: PathBuf,
@@ -32,7 +82,25 @@ pub async fn update(
     port: u16,
     tx: Sender<EmbeddingJob>,
     update_interval: u64,
-) -> Result<()> {    
+) -> Result<()> {
+    update_with_fs(root_path, root_id, port, tx, update_interval, Arc::new(OsFs), DEFAULT_WALK_CONCURRENCY).await
+}
+
+/// Same as `update`, but checks file kinds, modified times and contents
+/// through `fs` instead of the real OS, and reconciles at most `concurrency`
+/// entries at a time. The directory listing itself still comes from
+/// `WalkBuilder` on the real disk, and rename detection hashes files with
+/// `std::fs`, so only the per-file decisions can be driven from a `FakeFs`.
+#[async_recursion]
+pub async fn update_with_fs(
+    root_path: PathBuf,
+    root_id: String,
+    port: u16,
+    tx: Sender<EmbeddingJob>,
+    update_interval: u64,
+    fs_impl: Arc<dyn Fs>,
+    concurrency: usize,
+) -> Result<()> {
     // Load index types
     let index_types = fs::read_to_string("index-types.json")?;
     let index_types: serde_json::Value = serde_json::from_str(&index_types)?;
@@ -72,13 +140,41 @@ pub async fn update(
         walker_builder.add_ignore(pattern);
     }
 
-    // Collect entries to process
+    // Collect this level's entries once (the rename pre-pass and the
+    // reconciliation stream below both walk them), and keep a plain set of
+    // names around for the unseen-folder/unseen-file checks below instead of
+    // re-scanning the walk result for each one.
     let entries: Vec<_> = walker_builder.build()
         .filter_map(|result| result.ok())
         .filter(|entry| entry.path() != root_path)
         .collect();
+    let entry_names: HashSet<String> = entries.iter()
+        .filter_map(|entry| entry.path().file_name().and_then(|s| s.to_str()).map(|s| s.to_string()))
+        .collect();
+
+    // Match files that disappeared under their old name against files that
+    // appeared under a new one by content hash, so a plain rename/move
+    // doesn't purge and re-ingest from scratch.
+    let unseen_files_precheck: Vec<String> = unseen_entries(root_file_name_ids.keys(), &entry_names);
+    let mut candidate_new_paths = Vec::new();
+    for entry in &entries {
+        let path_buf = entry.path().to_path_buf();
+        if fs_impl.is_file(&path_buf).await {
+            let file_name = path_buf.file_name().unwrap().to_str().unwrap();
+            if !root_file_name_ids.contains_key(file_name) {
+                candidate_new_paths.push(path_buf);
+            }
+        }
+    }
+    let (_renames, unseen_files, remaining_new_paths) =
+        crate::rename::detect_renames(unseen_files_precheck, &root_file_name_ids, candidate_new_paths.clone(), port).await?;
+    let remaining_new_set: HashSet<_> = remaining_new_paths.into_iter().collect();
+    let renamed_new_names: HashSet<String> = candidate_new_paths.into_iter()
+        .filter(|p| !remaining_new_set.contains(p))
+        .filter_map(|p| p.file_name().and_then(|s| s.to_str()).map(|s| s.to_string()))
+        .collect();
 
-    let tasks: Vec<JoinHandle<Result<()>>> = entries.clone().clone().into_iter().map(|entry| {
+    let results: Vec<Result<()>> = stream::iter(entries).map(|entry| {
         let path_buf = entry.path().to_path_buf();
         let index_types_clone = index_types.clone();
         let root_folder_name_ids_clone = root_folder_name_ids.clone();
@@ -86,74 +182,78 @@ pub async fn update(
         let root_id_clone = root_id.clone();
         let tx_clone = tx.clone();
         let file_types_clone = file_types.clone();
-        
-        tokio::spawn(async move {
+        let fs_clone = fs_impl.clone();
+        let renamed_new_names_clone = renamed_new_names.clone();
+
+        async move {
             // Folder
-            if path_buf.is_dir(){
+            if fs_clone.is_dir(&path_buf).await {
                 let folder_name = path_buf.file_name().unwrap().to_str().unwrap();
                 if root_folder_name_ids_clone.contains_key(folder_name){
                     // println!("Folder {} already exists", folder_name);
                     let folder_id = root_folder_name_ids_clone.get(folder_name).unwrap().to_string();
-                    let _ = Box::pin(update_folder(path_buf.clone(), folder_id.clone(), port, index_types_clone, file_types_clone, tx_clone, update_interval)).await;
+                    if let Err(e) = Box::pin(update_folder(path_buf.clone(), folder_id.clone(), port, index_types_clone, file_types_clone, tx_clone, update_interval, fs_clone, concurrency)).await {
+                        eprintln!("Failed to reconcile folder {}: {}", folder_name, e);
+                    }
                 } else {
                     println!("Folder {} does not exist", folder_name);
-                    let _ = populate(path_buf.clone(), root_id_clone, port, true, index_types_clone, file_types_clone, tx_clone).await;
+                    let _ = populate(
+                        path_buf.clone(), root_id_clone, port, true, index_types_clone, file_types_clone, tx_clone, None, None,
+                        Arc::new(tokio::sync::Semaphore::new(concurrency)), concurrency, None, true,
+                    ).await;
                 }
                 Ok(())
 
             // File
-            } else if path_buf.is_file() {
+            } else if fs_clone.is_file(&path_buf).await {
                 let file_name = path_buf.file_name().unwrap().to_str().unwrap();
-                
+
                 if root_file_name_ids_clone.contains_key(file_name){
                     let file_id = root_file_name_ids_clone.get(file_name).unwrap().0.to_string();
                     let file_extracted_at = root_file_name_ids_clone.get(file_name).unwrap().1.to_string();
-                    let metadata = fs::metadata(&path_buf).expect("Failed to get metadata");
-                    if let Ok(last_modified) = metadata.modified() {
-                        let date_modified = DateTime::<Utc>::from(last_modified);
-                        
-                        let date_extracted = DateTime::parse_from_rfc3339(&file_extracted_at)
-                            .expect("Failed to parse date")
-                            .with_timezone(&Utc);
-
-                        let diff_sec = date_modified.signed_duration_since(date_extracted).num_seconds();
-                        if diff_sec > update_interval.try_into().unwrap() {
+                    match reconcile_file_action(fs_clone.as_ref(), &path_buf, &file_extracted_at, update_interval).await {
+                        FileReconcileAction::OutOfDate => {
                             println!("File {} is out of date", file_name);
                             let _ = update_file(
                                 path_buf,file_id,port,
-                                index_types_clone,file_types_clone,tx_clone
+                                index_types_clone,file_types_clone,tx_clone,
+                                fs_clone, concurrency,
+                            ).await;
+                        }
+                        FileReconcileAction::ModifiedTimeUnavailable => {
+                            println!("File {} last modified time not available", file_name);
+                            let _ = update_file(
+                                path_buf,file_id,port,
+                                index_types_clone,file_types_clone,tx_clone,
+                                fs_clone, concurrency,
                             ).await;
                         }
-                    } else {
-                        println!("File {} last modified time not available", file_name);
-                        let _ = update_file(
-                            path_buf,file_id,port,
-                            index_types_clone,file_types_clone,tx_clone
-                        ).await;
+                        FileReconcileAction::UpToDate => {}
                     }
+                } else if renamed_new_names_clone.contains(file_name) {
+                    // Already handled as a rename: same id, new name, no
+                    // re-chunking or re-embedding needed.
                 } else {
                     println!("File {} does not exist", file_name);
                     let _ = process_file(
-                        path_buf, root_id_clone, true, 
-                        port, index_types_clone, file_types_clone, tx_clone
+                        path_buf, root_id_clone, true,
+                        port, index_types_clone, file_types_clone, tx_clone, None, None,
+                        Arc::new(tokio::sync::Semaphore::new(concurrency)), concurrency, None,
                     ).await;
                 }
                 Ok(())
             } else {
                 Ok(())
             }
-        })
-    }).collect();
+        }
+    }).buffer_unordered(concurrency).collect().await;
 
-    for task in tasks {
-        task.await??;
+    for result in results {
+        result?;
     }
 
     // Find folders that are not in the index
-    let unseen_folders: Vec<String> = root_folder_name_ids.keys()
-        .filter(|folder_name| !entries.clone().iter().any(|entry| entry.path().file_name().unwrap().to_str().unwrap() == **folder_name))
-        .cloned()
-        .collect();
+    let unseen_folders: Vec<String> = unseen_entries(root_folder_name_ids.keys(), &entry_names);
 
     let delete_folder_tasks: Vec<JoinHandle<Result<()>>> = unseen_folders.into_iter().map(|folder_name| {
         let folder_id = root_folder_name_ids.get(&folder_name).unwrap().to_string().clone();
@@ -167,16 +267,161 @@ pub async fn update(
         task.await??;
     }
 
-    let unseen_files = root_file_name_ids.keys()
-        .filter(|file_name| !entries.iter().any(|entry| entry.path().file_name().unwrap().to_str().unwrap() == **file_name))
-        .cloned()
-        .collect::<Vec<_>>();
-
     delete_files(unseen_files, root_file_name_ids, port).await?;
 
     Ok(())
 }
 
+/// Drives incremental re-indexing from `git diff` instead of a full
+/// mtime-vs-`extracted_at` walk: for a Git-tracked root that the server has
+/// already indexed once, this restricts reconciliation to exactly the
+/// paths that changed since the last indexed commit. Falls back to the
+/// regular mtime-based `update` for untracked roots, first-time indexing,
+/// and any changed path whose containing folder isn't indexed yet (a new
+/// folder is cheaper to pick up via the normal walk than to special-case
+/// here).
+pub async fn update_git_aware(
+    root_path: PathBuf,
+    root_id: String,
+    port: u16,
+    tx: Sender<EmbeddingJob>,
+    update_interval: u64,
+) -> Result<()> {
+    let repo = match crate::git_sync::open_repo(&root_path) {
+        Some(repo) => repo,
+        None => return update(root_path, root_id, port, tx, update_interval).await,
+    };
+
+    let stored_sha = get_root_commit_sha(root_id.clone(), port).await?;
+    let diffable_sha = stored_sha.filter(|sha| {
+        git2::Oid::from_str(sha).ok().map(|oid| repo.find_commit(oid).is_ok()).unwrap_or(false)
+    });
+
+    match diffable_sha {
+        Some(sha) => {
+            let changes = crate::git_sync::changes_since(&repo, &sha, &root_path)?;
+            let needs_walk = apply_git_changes(&root_path, &root_id, port, tx.clone(), changes).await?;
+            if needs_walk {
+                update(root_path.clone(), root_id.clone(), port, tx, update_interval).await?;
+            }
+        }
+        None => {
+            update(root_path.clone(), root_id.clone(), port, tx, update_interval).await?;
+        }
+    }
+
+    let head = crate::git_sync::head_sha(&repo)?;
+    set_root_commit_sha(root_id, head, port).await?;
+    Ok(())
+}
+
+/// Resolves the folder id indexed for the directory at `dir` (relative to
+/// `root_path`), walking one `get_sub_folders` call per path component.
+/// Returns `None` if any ancestor along the way isn't indexed.
+pub(crate) async fn resolve_folder_id(root_path: &PathBuf, root_id: &str, dir: &std::path::Path, port: u16) -> Result<Option<String>> {
+    if dir == root_path.as_path() {
+        return Ok(Some(root_id.to_string()));
+    }
+    let relative = match dir.strip_prefix(root_path) {
+        Ok(relative) => relative,
+        Err(_) => return Ok(None),
+    };
+
+    let mut current_id = root_id.to_string();
+    let mut subfolders = get_root_folders(root_id.to_string(), port).await?;
+    for component in relative.components() {
+        let name = match component.as_os_str().to_str() {
+            Some(name) => name,
+            None => return Ok(None),
+        };
+        match subfolders.get(name) {
+            Some(id) => {
+                current_id = id.clone();
+                subfolders = get_sub_folders(current_id.clone(), port).await?;
+            }
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(current_id))
+}
+
+async fn apply_git_changes(
+    root_path: &PathBuf,
+    root_id: &str,
+    port: u16,
+    tx: Sender<EmbeddingJob>,
+    changes: crate::git_sync::GitChanges,
+) -> Result<bool> {
+    let index_types = fs::read_to_string("index-types.json")?;
+    let index_types: Arc<serde_json::Value> = Arc::new(serde_json::from_str(&index_types)?);
+    let file_types = fs::read_to_string("file_types.json")?;
+    let file_types: Arc<serde_json::Value> = Arc::new(serde_json::from_str(&file_types)?);
+
+    // Set when a changed path can't be applied here, so the caller runs the
+    // regular walk to pick it up.
+    let mut needs_walk = false;
+    for path in changes.changed {
+        if path.is_dir() {
+            needs_walk = true;
+            continue;
+        }
+        let parent = path.parent().unwrap_or(root_path.as_path());
+        let folder_id = match resolve_folder_id(root_path, root_id, parent, port).await? {
+            Some(id) => id,
+            None => {
+                println!("Skipping {} via git diff: containing folder isn't indexed yet", path.display());
+                needs_walk = true;
+                continue;
+            }
+        };
+        let file_name = match path.file_name().and_then(|s| s.to_str()) {
+            Some(name) => name,
+            None => continue,
+        };
+        let is_root_folder = folder_id == root_id;
+        let file_name_ids = if is_root_folder {
+            get_root_files(root_id.to_string(), port).await?
+        } else {
+            get_folder_files(folder_id.clone(), port).await?
+        };
+
+        if let Some((file_id, _)) = file_name_ids.get(file_name) {
+            update_file(
+                path, file_id.clone(), port, index_types.clone(), file_types.clone(), tx.clone(), Arc::new(OsFs),
+                DEFAULT_WALK_CONCURRENCY,
+            ).await?;
+        } else {
+            process_file(
+                path, folder_id, is_root_folder, port, index_types.clone(), file_types.clone(), tx.clone(), None, None,
+                Arc::new(tokio::sync::Semaphore::new(DEFAULT_WALK_CONCURRENCY)), DEFAULT_WALK_CONCURRENCY, None,
+            ).await?;
+        }
+    }
+
+    for path in changes.deleted {
+        let parent = path.parent().unwrap_or(root_path.as_path());
+        let folder_id = match resolve_folder_id(root_path, root_id, parent, port).await? {
+            Some(id) => id,
+            None => continue,
+        };
+        let file_name = match path.file_name().and_then(|s| s.to_str()) {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+        let is_root_folder = folder_id == root_id;
+        let file_name_ids = if is_root_folder {
+            get_root_files(root_id.to_string(), port).await?
+        } else {
+            get_folder_files(folder_id, port).await?
+        };
+        if file_name_ids.contains_key(&file_name) {
+            delete_files(vec![file_name], file_name_ids, port).await?;
+        }
+    }
+
+    Ok(needs_walk)
+}
+
 #[async_recursion]
 pub async fn update_folder(
     current_path: PathBuf,
@@ -186,6 +431,8 @@ pub async fn update_folder(
     file_types: Arc<serde_json::Value>,
     tx: Sender<EmbeddingJob>,
     update_interval: u64,
+    fs_impl: Arc<dyn Fs>,
+    concurrency: usize,
 ) -> Result<()> {
     let subfolder_name_ids  = get_sub_folders(folder_id.clone(), port).await?;
     // println!("Subfolder IDs: {:#?}", subfolder_name_ids);
@@ -201,13 +448,41 @@ pub async fn update_folder(
         walker_builder.add_ignore(pattern);
     }
 
-    // Collect entries to process
+    // Collect this level's entries once (the rename pre-pass and the
+    // reconciliation stream below both walk them), and keep a plain set of
+    // names around for the unseen-folder/unseen-file checks below instead of
+    // re-scanning the walk result for each one.
     let entries: Vec<_> = walker_builder.build()
         .filter_map(|result| result.ok())
         .filter(|entry| entry.path() != current_path)
         .collect();
+    let entry_names: HashSet<String> = entries.iter()
+        .filter_map(|entry| entry.path().file_name().and_then(|s| s.to_str()).map(|s| s.to_string()))
+        .collect();
+
+    // Match files that disappeared under their old name against files that
+    // appeared under a new one by content hash, so a plain rename/move
+    // doesn't purge and re-ingest from scratch.
+    let unseen_files_precheck: Vec<String> = unseen_entries(folder_file_name_ids.keys(), &entry_names);
+    let mut candidate_new_paths = Vec::new();
+    for entry in &entries {
+        let path_buf = entry.path().to_path_buf();
+        if fs_impl.is_file(&path_buf).await {
+            let file_name = path_buf.file_name().unwrap().to_str().unwrap();
+            if !folder_file_name_ids.contains_key(file_name) {
+                candidate_new_paths.push(path_buf);
+            }
+        }
+    }
+    let (_renames, unseen_files, remaining_new_paths) =
+        crate::rename::detect_renames(unseen_files_precheck, &folder_file_name_ids, candidate_new_paths.clone(), port).await?;
+    let remaining_new_set: HashSet<_> = remaining_new_paths.into_iter().collect();
+    let renamed_new_names: HashSet<String> = candidate_new_paths.into_iter()
+        .filter(|p| !remaining_new_set.contains(p))
+        .filter_map(|p| p.file_name().and_then(|s| s.to_str()).map(|s| s.to_string()))
+        .collect();
 
-    let tasks: Vec<JoinHandle<Result<()>>> = entries.clone().clone().into_iter().map(|entry| {
+    let results: Vec<Result<()>> = stream::iter(entries).map(|entry| {
         let path_buf = entry.path().to_path_buf();
         let index_types_clone = index_types.clone();
         let subfolder_name_ids_clone = subfolder_name_ids.clone();
@@ -215,73 +490,78 @@ pub async fn update_folder(
         let folder_id_clone = folder_id.clone();
         let file_types_clone = file_types.clone();
         let tx_clone = tx.clone();
+        let fs_clone = fs_impl.clone();
+        let renamed_new_names_clone = renamed_new_names.clone();
 
-        tokio::spawn(async move {
+        async move {
             // Folder
-            if path_buf.is_dir(){
+            if fs_clone.is_dir(&path_buf).await {
                 let folder_name = path_buf.file_name().unwrap().to_str().unwrap();
                 if subfolder_name_ids_clone.contains_key(folder_name){
                     // println!("Folder {} already exists", folder_name);
                     let sub_folder_id = subfolder_name_ids_clone.get(folder_name).unwrap().to_string();
-                    let _ = Box::pin(update_folder(path_buf.clone(), sub_folder_id, port, index_types_clone, file_types_clone, tx_clone, update_interval)).await;
+                    if let Err(e) = Box::pin(update_folder(path_buf.clone(), sub_folder_id, port, index_types_clone, file_types_clone, tx_clone, update_interval, fs_clone, concurrency)).await {
+                        eprintln!("Failed to reconcile folder {}: {}", folder_name, e);
+                    }
                 } else {
                     println!("Folder {} does not exist", folder_name);
-                    let _ = populate(path_buf.clone(), folder_id_clone, port, false, index_types_clone, file_types_clone, tx_clone).await;
+                    let _ = populate(
+                        path_buf.clone(), folder_id_clone, port, false, index_types_clone, file_types_clone, tx_clone, None, None,
+                        Arc::new(tokio::sync::Semaphore::new(concurrency)), concurrency, None, true,
+                    ).await;
                 }
                 Ok(())
 
             // File
-            } else if path_buf.is_file() {
+            } else if fs_clone.is_file(&path_buf).await {
                 let file_name = path_buf.file_name().unwrap().to_str().unwrap();
-                
+
                 if folder_file_name_ids_clone.contains_key(file_name){
                     let file_id = folder_file_name_ids_clone.get(file_name).unwrap().0.to_string();
                     let file_extracted_at = folder_file_name_ids_clone.get(file_name).unwrap().1.to_string();
-                    let metadata = fs::metadata(&path_buf).expect("Failed to get metadata");
-                    if let Ok(last_modified) = metadata.modified() {
-                        let date_modified = DateTime::<Utc>::from(last_modified);
-                        let date_extracted = DateTime::parse_from_rfc3339(&file_extracted_at)
-                            .expect("Failed to parse date")
-                            .with_timezone(&Utc);
-
-                        let diff_sec = date_modified.signed_duration_since(date_extracted).num_seconds();
-                        if diff_sec > update_interval.try_into().unwrap() {
+                    match reconcile_file_action(fs_clone.as_ref(), &path_buf, &file_extracted_at, update_interval).await {
+                        FileReconcileAction::OutOfDate => {
                             println!("File {} is out of date", file_name);
                             let _ = update_file(
                                 path_buf, file_id, port,
                                 index_types_clone, file_types_clone, tx_clone,
+                                fs_clone, concurrency,
+                            ).await;
+                        }
+                        FileReconcileAction::ModifiedTimeUnavailable => {
+                            println!("File {} last modified time not available", file_name);
+                            let _ = update_file(
+                                path_buf, file_id, port,
+                                index_types_clone, file_types_clone, tx_clone,
+                                fs_clone, concurrency,
                             ).await;
                         }
-                    } else {
-                        println!("File {} last modified time not available", file_name);
-                        let _ = update_file(
-                            path_buf, file_id, port,
-                            index_types_clone, file_types_clone, tx_clone,
-                        ).await;
+                        FileReconcileAction::UpToDate => {}
                     }
+                } else if renamed_new_names_clone.contains(file_name) {
+                    // Already handled as a rename: same id, new name, no
+                    // re-chunking or re-embedding needed.
                 } else {
                     println!("File {} does not exist", file_name);
                     let _ = process_file(
                         path_buf, folder_id_clone, false, port,
-                        index_types_clone, file_types_clone, tx_clone
+                        index_types_clone, file_types_clone, tx_clone, None, None,
+                        Arc::new(tokio::sync::Semaphore::new(concurrency)), concurrency, None,
                     ).await;
                 }
                 Ok(())
             } else {
                 Ok(())
             }
-        })
-    }).collect();
+        }
+    }).buffer_unordered(concurrency).collect().await;
 
-    for task in tasks {
-        task.await??;
+    for result in results {
+        result?;
     }
 
     // Find folders that are not in the index
-    let unseen_folders: Vec<String> = subfolder_name_ids.keys()
-        .filter(|folder_name| !entries.clone().iter().any(|entry| entry.path().file_name().unwrap().to_str().unwrap() == **folder_name))
-        .cloned()
-        .collect();
+    let unseen_folders: Vec<String> = unseen_entries(subfolder_name_ids.keys(), &entry_names);
 
     let delete_folder_tasks: Vec<JoinHandle<Result<()>>> = unseen_folders.into_iter().map(|folder_name| {
         let folder_id_clone = subfolder_name_ids.get(&folder_name).unwrap().to_string().clone();
@@ -295,11 +575,6 @@ pub async fn update_folder(
         task.await??;
     }
 
-    let unseen_files = folder_file_name_ids.keys()
-        .filter(|file_name| !entries.iter().any(|entry| entry.path().file_name().unwrap().to_str().unwrap() == **file_name))
-        .cloned()
-        .collect::<Vec<_>>();
-
     delete_files(unseen_files, folder_file_name_ids, port).await?;
 
     Ok(())
@@ -312,8 +587,13 @@ pub async fn update_file(
     index_types: Arc<serde_json::Value>,
     file_types: Arc<serde_json::Value>,
     tx: Sender<EmbeddingJob>,
+    fs_impl: Arc<dyn Fs>,
+    concurrency: usize,
 ) -> Result<()> {
-    let source_code = match fs::read_to_string(&file_path) {
+    // Bounds how many entity/chunk creation requests this file's re-ingestion
+    // can have in flight at once.
+    let semaphore = Arc::new(tokio::sync::Semaphore::new(concurrency));
+    let source_code = match fs_impl.read_to_string(&file_path).await {
         Ok(source_code) => source_code,
         Err(e) => {
             eprintln!("Skipped {}: {}", file_path.file_name().unwrap().to_str().unwrap(), e);
@@ -354,7 +634,10 @@ pub async fn update_file(
         // Process entities
         let root_node = tree.root_node();
         let owned_nodes = build_owned_nodes(root_node, &source_code);
-        ingest_entities(owned_nodes, file_id.to_string(), port, extension.to_string(), index_types, tx).await?;
+        ingest_entities(
+            owned_nodes, file_id.to_string(), port, extension.to_string(), index_types, tx,
+            semaphore, concurrency, None, Some(language.clone()),
+        ).await?;
     // File is not supported by Tree Sitter
     } else {
         // Create file without entities
@@ -377,8 +660,93 @@ pub async fn update_file(
         let order_counter = Arc::new(AtomicUsize::new(1));
         TOTAL_CHUNKS.fetch_add(chunks.len(), Ordering::SeqCst);
 
-        process_unsupported_file(chunks, file_id.to_string(), port, order_counter, tx).await?;
+        process_unsupported_file(
+            chunks, file_id.to_string(), port, order_counter, tx, semaphore, concurrency, None,
+        ).await?;
     }
-    
+
     Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::FakeFs;
+    use std::time::{Duration, SystemTime};
+
+    fn extracted_at(offset: Duration) -> (SystemTime, String) {
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let extracted = modified - offset;
+        (modified, DateTime::<Utc>::from(extracted).to_rfc3339())
+    }
+
+    #[tokio::test]
+    async fn up_to_date_file_is_left_alone() {
+        let (modified, extracted_at) = extracted_at(Duration::from_secs(5));
+        let fs = FakeFs::new().with_file("/root/a.rs", "fn a() {}", modified);
+
+        let action = reconcile_file_action(&fs, Path::new("/root/a.rs"), &extracted_at, 3600).await;
+
+        assert_eq!(action, FileReconcileAction::UpToDate);
+    }
+
+    #[tokio::test]
+    async fn file_modified_past_the_update_interval_is_out_of_date() {
+        let (modified, extracted_at) = extracted_at(Duration::from_secs(7200));
+        let fs = FakeFs::new().with_file("/root/a.rs", "fn a() {}", modified);
+
+        let action = reconcile_file_action(&fs, Path::new("/root/a.rs"), &extracted_at, 3600).await;
+
+        assert_eq!(action, FileReconcileAction::OutOfDate);
+    }
+
+    #[tokio::test]
+    async fn missing_modified_time_falls_back_to_reingest() {
+        let fs = FakeFs::new().with_dir("/root");
+
+        let action = reconcile_file_action(&fs, Path::new("/root/gone.rs"), &Utc::now().to_rfc3339(), 3600).await;
+
+        assert_eq!(action, FileReconcileAction::ModifiedTimeUnavailable);
+    }
+
+    #[tokio::test]
+    async fn set_modified_moves_a_file_back_into_date() {
+        let (stale_modified, extracted_at) = extracted_at(Duration::from_secs(7200));
+        let fs = FakeFs::new().with_file("/root/a.rs", "fn a() {}", stale_modified);
+        let path = Path::new("/root/a.rs");
+        assert_eq!(
+            reconcile_file_action(&fs, path, &extracted_at, 3600).await,
+            FileReconcileAction::OutOfDate
+        );
+
+        // A later re-extraction without a matching disk edit would leave
+        // `modified` no newer than `extracted_at` - confirm mutating the
+        // fake between calls is actually observed on the next check.
+        let fresh_modified = stale_modified - Duration::from_secs(7200);
+        fs.set_modified(path, fresh_modified);
+
+        assert_eq!(
+            reconcile_file_action(&fs, path, &extracted_at, 3600).await,
+            FileReconcileAction::UpToDate
+        );
+    }
+
+    #[test]
+    fn unseen_entries_reports_names_missing_from_the_walk() {
+        let known: HashSet<String> = ["a.rs".to_string(), "b.rs".to_string(), "c.rs".to_string()].into();
+        let seen: HashSet<String> = ["b.rs".to_string()].into();
+
+        let mut unseen = unseen_entries(known.iter(), &seen);
+        unseen.sort();
+
+        assert_eq!(unseen, vec!["a.rs".to_string(), "c.rs".to_string()]);
+    }
+
+    #[test]
+    fn unseen_entries_is_empty_once_everything_is_seen() {
+        let known: HashSet<String> = ["a.rs".to_string()].into();
+        let seen: HashSet<String> = ["a.rs".to_string()].into();
+
+        assert!(unseen_entries(known.iter(), &seen).is_empty());
+    }
 }
\ No newline at end of file
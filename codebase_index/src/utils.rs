@@ -35,13 +35,6 @@ pub struct EmbeddingJob {
 
 // Global HTTP client with connection pooling
 lazy_static! {
-    static ref embedding_client: reqwest::Client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(30))
-        .pool_max_idle_per_host(3000)
-        .pool_idle_timeout(Duration::from_secs(30))
-        .build()
-        .expect("Failed to create HTTP client");
-
     static ref helix_client: reqwest::Client = reqwest::Client::builder()
         .timeout(Duration::from_secs(90))
         .pool_max_idle_per_host(500)
@@ -49,11 +42,12 @@ lazy_static! {
         .build()
         .expect("Failed to create HTTP client");
 
-    static ref EMBEDDING_LIMITER: RateLimiter<NotKeyed, InMemoryState, DefaultClock> =
-        RateLimiter::direct(Quota::per_minute(NonZeroU32::new(4000).unwrap()));
-
     static ref HELIX_LIMITER: RateLimiter<NotKeyed, InMemoryState, DefaultClock> =
         RateLimiter::direct(Quota::per_second(NonZeroU32::new(100).unwrap()));
+
+    // Trips after 5 consecutive failures on an endpoint, cools down for 30s
+    static ref HELIX_CIRCUIT_BREAKERS: crate::resilience::CircuitBreakerRegistry =
+        crate::resilience::CircuitBreakerRegistry::new(5, Duration::from_secs(30));
 }
 
 // Chunk entity text
@@ -65,87 +59,176 @@ pub fn chunk_entity(text: &str) -> Result<Vec<String>> {
     Ok(chunks_str)
 }
 
-// Async version of embed_entity with rate limiting
-pub async fn embed_entity_async(text: String) -> Result<Vec<f64>> {
-    // Handle empty text case to avoid API errors
-    if text.trim().is_empty() {
-        return Err(anyhow::anyhow!("Cannot embed empty text"));
-    }
-
-    EMBEDDING_LIMITER.until_ready().await;
+// Node kinds treated as semantic chunk boundaries across the supported grammars
+const SEMANTIC_NODE_KINDS: &[&str] = &[
+    "function_item", "function_definition", "function_declaration",
+    "impl_item", "class_definition", "class_declaration",
+    "struct_item", "enum_item", "trait_item", "mod_item",
+    "method_definition", "method_declaration",
+];
+
+// Syntax-aware chunking: walks the tree-sitter parse tree and emits one chunk
+// per semantic node (function/class/impl/etc.) instead of splitting on a flat
+// character budget, so embeddings don't cut a declaration in half
+pub fn chunk_code(text: &str, lang: tree_sitter::Language, max_chars: usize) -> Result<Vec<CodeEntity>> {
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&lang)?;
+    let tree = parser.parse(text, None).ok_or_else(|| anyhow::anyhow!("Failed to parse source for chunking"))?;
+
+    let mut chunks = Vec::new();
+    let mut order = 1;
+    walk_for_chunks(tree.root_node(), text, max_chars, None, &mut chunks, &mut order);
+    Ok(chunks)
+}
 
-    // Use gemini api to embed text with the global HTTP client
-    let api_key = match env::var("GEMINI_API_KEY") {
-        Ok(key) => key,
-        Err(_) => return Err(anyhow::anyhow!("GEMINI_API_KEY environment variable not set"))
+fn walk_for_chunks(
+    node: tree_sitter::Node,
+    source: &str,
+    max_chars: usize,
+    enclosing_signature: Option<String>,
+    chunks: &mut Vec<CodeEntity>,
+    order: &mut usize,
+) {
+    let mut cursor = node.walk();
+    let mut pending_start: Option<usize> = None;
+    let mut pending_end: Option<usize> = None;
+
+    let flush_pending = |pending_start: &mut Option<usize>, pending_end: &mut Option<usize>, chunks: &mut Vec<CodeEntity>, order: &mut usize| {
+        if let (Some(start), Some(end)) = (*pending_start, *pending_end) {
+            chunks.push(CodeEntity {
+                entity_type: "coalesced".to_string(),
+                start_byte: start,
+                end_byte: end,
+                order: *order,
+                text: source[start..end].to_string(),
+            });
+            *order += 1;
+        }
+        *pending_start = None;
+        *pending_end = None;
     };
 
-    let res = embedding_client.post("https://generativelanguage.googleapis.com/v1beta/models/gemini-embedding-001:embedContent")
-        .header("x-goog-api-key", api_key)
-        .header("Content-Type", "application/json")
-        .json(&json!({
-            "model": "models/gemini-embedding-001",
-            "content": {
-                "parts": [{
-                    "text": text,
-                }]
-            },
-            "task_type": "SEMANTIC_SIMILARITY"
-        }))
-        .send()
-        .await?;
-
-    // Check response status
-    if !res.status().is_success() {
-        let status = res.status();
-        let error_text = res.text().await.unwrap_or_else(|_| "<could not read response body>".to_string());
-        return Err(anyhow::anyhow!("API returned error status {}: {}", status, error_text));
+    for child in node.children(&mut cursor) {
+        let span = child.end_byte() - child.start_byte();
+
+        if SEMANTIC_NODE_KINDS.contains(&child.kind()) {
+            flush_pending(&mut pending_start, &mut pending_end, chunks, order);
+
+            let body = &source[child.start_byte()..child.end_byte()];
+            if span > max_chars && child.named_child_count() > 0 {
+                // Too large to embed whole: prepend this node's signature as
+                // context and recurse into its children.
+                let signature = signature_line(body);
+                walk_for_chunks(child, source, max_chars, Some(signature), chunks, order);
+            } else {
+                let text = match &enclosing_signature {
+                    Some(sig) => format!("{}\n{}", sig, body),
+                    None => body.to_string(),
+                };
+                chunks.push(CodeEntity {
+                    entity_type: child.kind().to_string(),
+                    start_byte: child.start_byte(),
+                    end_byte: child.end_byte(),
+                    order: *order,
+                    text,
+                });
+                *order += 1;
+            }
+        } else if span > 0 {
+            // Greedily coalesce consecutive small non-semantic siblings
+            // (imports, comments, statements) into a single chunk.
+            let candidate_end = child.end_byte();
+            let candidate_start = pending_start.unwrap_or(child.start_byte());
+            if candidate_end - candidate_start <= max_chars {
+                pending_start = Some(candidate_start);
+                pending_end = Some(candidate_end);
+            } else {
+                flush_pending(&mut pending_start, &mut pending_end, chunks, order);
+                pending_start = Some(child.start_byte());
+                pending_end = Some(candidate_end);
+            }
+        }
     }
 
-    let body = res.json::<Value>().await?;
-    
-    // More detailed error handling for the response format
-    if !body.is_object() {
-        return Err(anyhow::anyhow!("API response is not a JSON object: {:?}", body));
-    }
-    
-    if !body.get("embedding").is_some() {
-        return Err(anyhow::anyhow!("API response missing 'embedding' field: {:?}", body));
-    }
-    
-    let embedding = body["embedding"]["values"].as_array()
-        .ok_or_else(|| anyhow::anyhow!("Invalid embedding response format, missing 'values' array: {:?}", body))?;
-
-    // Convert values to f64, with better error handling
-    let mut result = Vec::with_capacity(embedding.len());
-    for (i, v) in embedding.iter().enumerate() {
-        match v.as_f64() {
-            Some(val) => result.push(val),
-            None => return Err(anyhow::anyhow!("Non-numeric value at position {} in embedding: {:?}", i, v))
+    flush_pending(&mut pending_start, &mut pending_end, chunks, order);
+}
+
+// Extracts the first line of a declaration (its signature/header) to use as
+// context prepended to child chunks split out of an oversized node
+fn signature_line(body: &str) -> String {
+    body.lines().next().unwrap_or(body).trim().to_string()
+}
+
+// Matches the character budget `chunk_entity` hands its `RecursiveChunker`,
+// so routing an entity through the syntax-aware chunker instead doesn't
+// change how big an embedded chunk can get.
+const SEMANTIC_CHUNK_MAX_CHARS: usize = 2048;
+
+/// Chunks an already-extracted entity's text for embedding. Prefers the
+/// syntax-aware `chunk_code` chunker when the entity's source language is
+/// known, so chunks land on semantic boundaries instead of a flat character
+/// cut; falls back to `chunk_entity` when no language was detected for the
+/// file (or parsing the entity's text back out fails). A syntax-aware chunk
+/// that still exceeds the budget is split further by `chunk_entity`.
+pub fn chunk_entity_for_language(text: &str, language: Option<tree_sitter::Language>) -> Result<Vec<String>> {
+    if let Some(language) = language {
+        if let Ok(chunks) = chunk_code(text, language, SEMANTIC_CHUNK_MAX_CHARS) {
+            let mut texts = Vec::with_capacity(chunks.len());
+            for chunk in chunks {
+                if chunk.text.chars().count() > SEMANTIC_CHUNK_MAX_CHARS {
+                    texts.extend(chunk_entity(&chunk.text)?);
+                } else {
+                    texts.push(chunk.text);
+                }
+            }
+            return Ok(texts);
         }
     }
-
-    Ok(result)
+    chunk_entity(text)
 }
 
 // Async version of post_request
 pub async fn post_request_async(url: &str, body: Value) -> Result<Value> {
-    HELIX_LIMITER.until_ready().await;
-
-    // Use the global HTTP client with connection pooling
-    let res = match helix_client.post(url).json(&body).send().await {
-        Ok(response) => response,
-        Err(e) => {
-            if e.is_timeout() {
-                println!("Request timed out. Check if the server is running and responding.");
-            } else if e.is_connect() {
-                println!("Connection failed. Make sure the server is running at {}",url);
+    use crate::resilience::RetryPolicy;
+
+    post_request_with_retry(url, body, RetryPolicy::default()).await
+}
+
+/// Same as `post_request_async`, but lets the caller pick its own
+/// `RetryPolicy` instead of `RetryPolicy::default()` — so a bulk ingestion
+/// run can tune attempts/backoff independently of one-off lookups.
+pub async fn post_request_with_retry(url: &str, body: Value, policy: crate::resilience::RetryPolicy) -> Result<Value> {
+    use crate::resilience::{classify_status, retry_with_backoff, FailureKind};
+
+    retry_with_backoff(url, &HELIX_CIRCUIT_BREAKERS, policy, || async {
+        HELIX_LIMITER.until_ready().await;
+
+        let res = match helix_client.post(url).json(&body).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                if e.is_timeout() {
+                    println!("Request timed out. Check if the server is running and responding.");
+                } else if e.is_connect() {
+                    println!("Connection failed. Make sure the server is running at {}", url);
+                }
+                return Err((FailureKind::Retryable, None, anyhow::anyhow!("HTTP request failed: {}", e)));
             }
-            return Err(anyhow::anyhow!("HTTP request failed: {}", e));
+        };
+
+        let status = res.status();
+        if !status.is_success() {
+            let kind = classify_status(status.as_u16());
+            let retry_after = res.headers()
+                .get("Retry-After")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok())
+                .map(Duration::from_secs);
+            let error_text = res.text().await.unwrap_or_else(|_| "<could not read response body>".to_string());
+            return Err((kind, retry_after, anyhow::anyhow!("HelixDB request returned {}: {}", status, error_text)));
         }
-    };
 
-    Ok(res.json::<Value>().await?)
+        res.json::<Value>().await.map_err(|e| (FailureKind::Retryable, None, anyhow::anyhow!("Failed to parse response: {}", e)))
+    }).await
 }
 
 // Get language from file extension
@@ -213,6 +296,34 @@ pub async fn delete_folder(
     Ok(())
 }
 
+/// Records the commit SHA that a root has been fully reconciled up to, so
+/// the next `update` run can ask Git for just the paths that changed since.
+pub async fn set_root_commit_sha(
+    root_id: String,
+    commit_sha: String,
+    port: u16,
+) -> Result<()> {
+    let url = format!("http://localhost:{}/{}", port, "setRootCommitSha");
+    let payload = json!({ "root_id": root_id, "commit_sha": commit_sha });
+    post_request_async(&url, payload).await?;
+    Ok(())
+}
+
+/// Updates a file's name/path in place and records its new content hash,
+/// keeping its id and existing entities so a rename/move skips re-chunking
+/// and re-embedding entirely.
+pub async fn rename_file(
+    file_id: String,
+    new_name: String,
+    new_hash: String,
+    port: u16,
+) -> Result<()> {
+    let url = format!("http://localhost:{}/{}", port, "renameFile");
+    let payload = json!({ "file_id": file_id, "name": new_name, "hash": new_hash });
+    post_request_async(&url, payload).await?;
+    Ok(())
+}
+
 pub async fn delete_files(
     unseen_files: Vec<String>,
     file_name_ids: HashMap<String, (String, String)>,
@@ -0,0 +1,157 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// The filesystem operations `updater` needs, pulled behind a trait so the
+/// exists/out-of-date/unseen reconciliation logic can be exercised against
+/// an in-memory tree instead of scratch directories and real clocks.
+#[async_trait]
+pub trait Fs: Send + Sync {
+    async fn read_to_string(&self, path: &Path) -> std::io::Result<String>;
+
+    /// Last-modified time, or `Err` if the platform/filesystem doesn't
+    /// report one (callers fall back to treating the file as out of date).
+    async fn modified(&self, path: &Path) -> std::io::Result<SystemTime>;
+
+    async fn is_dir(&self, path: &Path) -> bool;
+
+    async fn is_file(&self, path: &Path) -> bool;
+
+    /// Immediate children of `path`, non-recursive.
+    async fn read_dir(&self, path: &Path) -> std::io::Result<Vec<PathBuf>>;
+}
+
+/// The real, OS-backed implementation `update` runs against in production.
+pub struct OsFs;
+
+#[async_trait]
+impl Fs for OsFs {
+    async fn read_to_string(&self, path: &Path) -> std::io::Result<String> {
+        tokio::fs::read_to_string(path).await
+    }
+
+    async fn modified(&self, path: &Path) -> std::io::Result<SystemTime> {
+        tokio::fs::metadata(path).await?.modified()
+    }
+
+    async fn is_dir(&self, path: &Path) -> bool {
+        tokio::fs::metadata(path).await.map(|m| m.is_dir()).unwrap_or(false)
+    }
+
+    async fn is_file(&self, path: &Path) -> bool {
+        tokio::fs::metadata(path).await.map(|m| m.is_file()).unwrap_or(false)
+    }
+
+    async fn read_dir(&self, path: &Path) -> std::io::Result<Vec<PathBuf>> {
+        let mut entries = Vec::new();
+        let mut read_dir = tokio::fs::read_dir(path).await?;
+        while let Some(entry) = read_dir.next_entry().await? {
+            entries.push(entry.path());
+        }
+        Ok(entries)
+    }
+}
+
+#[derive(Clone)]
+enum FakeNode {
+    File { contents: String, modified: SystemTime },
+    Dir { children: Vec<PathBuf> },
+}
+
+/// An in-memory tree a caller builds up by hand and mutates between calls,
+/// so the per-file reconciliation decisions (up to date, out of date,
+/// missing modified time) can be driven deterministically without touching
+/// the disk.
+#[derive(Default)]
+pub struct FakeFs {
+    nodes: Mutex<HashMap<PathBuf, FakeNode>>,
+}
+
+impl FakeFs {
+    pub fn new() -> Self {
+        Self { nodes: Mutex::new(HashMap::new()) }
+    }
+
+    /// Inserts (or overwrites) a file with the given contents and modified
+    /// time, registering it as a child of its parent directory.
+    pub fn with_file(self, path: impl Into<PathBuf>, contents: impl Into<String>, modified: SystemTime) -> Self {
+        let path = path.into();
+        self.register_child(&path);
+        self.nodes.lock().unwrap().insert(
+            path,
+            FakeNode::File { contents: contents.into(), modified },
+        );
+        self
+    }
+
+    pub fn with_dir(self, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        self.register_child(&path);
+        self.nodes.lock().unwrap().entry(path).or_insert(FakeNode::Dir { children: Vec::new() });
+        self
+    }
+
+    pub fn set_modified(&self, path: &Path, modified: SystemTime) {
+        if let Some(FakeNode::File { modified: m, .. }) = self.nodes.lock().unwrap().get_mut(path) {
+            *m = modified;
+        }
+    }
+
+    pub fn remove(&self, path: &Path) {
+        let mut nodes = self.nodes.lock().unwrap();
+        nodes.remove(path);
+        if let Some(parent) = path.parent() {
+            if let Some(FakeNode::Dir { children }) = nodes.get_mut(parent) {
+                children.retain(|c| c != path);
+            }
+        }
+    }
+
+    fn register_child(&self, path: &Path) {
+        if let Some(parent) = path.parent() {
+            let mut nodes = self.nodes.lock().unwrap();
+            match nodes.entry(parent.to_path_buf()).or_insert(FakeNode::Dir { children: Vec::new() }) {
+                FakeNode::Dir { children } => {
+                    if !children.contains(&path.to_path_buf()) {
+                        children.push(path.to_path_buf());
+                    }
+                }
+                FakeNode::File { .. } => {}
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Fs for FakeFs {
+    async fn read_to_string(&self, path: &Path) -> std::io::Result<String> {
+        match self.nodes.lock().unwrap().get(path) {
+            Some(FakeNode::File { contents, .. }) => Ok(contents.clone()),
+            _ => Err(std::io::Error::new(std::io::ErrorKind::NotFound, "no such file in FakeFs")),
+        }
+    }
+
+    async fn modified(&self, path: &Path) -> std::io::Result<SystemTime> {
+        match self.nodes.lock().unwrap().get(path) {
+            Some(FakeNode::File { modified, .. }) => Ok(*modified),
+            _ => Err(std::io::Error::new(std::io::ErrorKind::NotFound, "no such file in FakeFs")),
+        }
+    }
+
+    async fn is_dir(&self, path: &Path) -> bool {
+        matches!(self.nodes.lock().unwrap().get(path), Some(FakeNode::Dir { .. }))
+    }
+
+    async fn is_file(&self, path: &Path) -> bool {
+        matches!(self.nodes.lock().unwrap().get(path), Some(FakeNode::File { .. }))
+    }
+
+    async fn read_dir(&self, path: &Path) -> std::io::Result<Vec<PathBuf>> {
+        match self.nodes.lock().unwrap().get(path) {
+            Some(FakeNode::Dir { children }) => Ok(children.clone()),
+            _ => Err(std::io::Error::new(std::io::ErrorKind::NotFound, "no such directory in FakeFs")),
+        }
+    }
+}
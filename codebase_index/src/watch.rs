@@ -0,0 +1,222 @@
+use anyhow::Result;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use notify::event::{CreateKind, ModifyKind, RemoveKind};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, Sender};
+use tokio::time::Instant;
+
+use crate::ingestion::{populate, process_file, DEFAULT_INGEST_CONCURRENCY};
+use crate::queries::{get_folder_files, get_root_files};
+use crate::updater::update_file;
+use crate::utils::{delete_files, delete_folder, EmbeddingJob};
+use crate::vfs::OsFs;
+
+/// How long to let filesystem events for the same path accumulate before
+/// dispatching, so an editor's write-temp-then-rename save collapses into a
+/// single `update_file` call instead of several.
+const DEBOUNCE_WINDOW: Duration = Duration::from_millis(200);
+
+/// Watches `root_path` for create/modify/delete events and keeps the index
+/// live instead of relying on periodic `update` re-walks. Callers should run
+/// an initial full `update`/`populate` pass before starting the watch so the
+/// server-side name -> id lookups below find what is already indexed.
+pub async fn watch_root(
+    root_path: PathBuf,
+    root_id: String,
+    port: u16,
+    index_types: Arc<serde_json::Value>,
+    file_types: Arc<serde_json::Value>,
+    tx: Sender<EmbeddingJob>,
+) -> Result<()> {
+    let ignore_matcher = build_ignore_matcher(&root_path);
+
+    let (raw_tx, mut raw_rx) = mpsc::channel::<Event>(1000);
+    let mut watcher: RecommendedWatcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+        if let Ok(event) = res {
+            let _ = raw_tx.blocking_send(event);
+        }
+    })?;
+    watcher.watch(&root_path, RecursiveMode::Recursive)?;
+
+    // Pending paths get coalesced here until `DEBOUNCE_WINDOW` has passed
+    // without a new event for that path.
+    let mut pending: HashMap<PathBuf, (EventKind, Instant)> = HashMap::new();
+    let mut ticker = tokio::time::interval(Duration::from_millis(50));
+
+    loop {
+        tokio::select! {
+            maybe_event = raw_rx.recv() => {
+                let event = match maybe_event {
+                    Some(event) => event,
+                    None => break,
+                };
+                for path in event.paths.iter() {
+                    if ignore_matcher.matched_path_or_any_parents(path, path.is_dir()).is_ignore() {
+                        continue;
+                    }
+                    pending.insert(path.clone(), (event.kind, Instant::now()));
+                }
+            }
+            _ = ticker.tick() => {
+                let ready: Vec<PathBuf> = pending.iter()
+                    .filter(|(_, (_, seen_at))| seen_at.elapsed() >= DEBOUNCE_WINDOW)
+                    .map(|(path, _)| path.clone())
+                    .collect();
+
+                for path in ready {
+                    if let Some((kind, _)) = pending.remove(&path) {
+                        if let Err(e) = dispatch_event(
+                            kind,
+                            &path,
+                            &root_path,
+                            &root_id,
+                            port,
+                            index_types.clone(),
+                            file_types.clone(),
+                            tx.clone(),
+                        ).await {
+                            eprintln!("Failed to handle watch event for {}: {}", path.display(), e);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Maps a single coalesced filesystem event to the matching index action.
+/// Renames are intentionally not special-cased here: notify reports them as
+/// a name-change `Modify` on each side, which is handled as a `Remove` on the
+/// old path plus a `Create` (directory) or `Modify` (file) on the new one.
+async fn dispatch_event(
+    kind: EventKind,
+    path: &Path,
+    root_path: &Path,
+    root_id: &str,
+    port: u16,
+    index_types: Arc<serde_json::Value>,
+    file_types: Arc<serde_json::Value>,
+    tx: Sender<EmbeddingJob>,
+) -> Result<()> {
+    let path_buf = path.to_path_buf();
+
+    let kind = match kind {
+        EventKind::Modify(ModifyKind::Name(_)) if !path_buf.exists() => EventKind::Remove(RemoveKind::Any),
+        EventKind::Modify(ModifyKind::Name(_)) if path_buf.is_dir() => EventKind::Create(CreateKind::Folder),
+        other => other,
+    };
+
+    match kind {
+        EventKind::Create(_) => {
+            if path_buf.is_dir() {
+                let parent_id = parent_folder_id(path, root_path, root_id, port).await?;
+                populate(
+                    path_buf, parent_id, port, path.parent() == Some(root_path), index_types, file_types, tx, None, None,
+                    Arc::new(tokio::sync::Semaphore::new(DEFAULT_INGEST_CONCURRENCY)), DEFAULT_INGEST_CONCURRENCY, None, true,
+                ).await
+            } else if path_buf.is_file() {
+                let parent_id = parent_folder_id(path, root_path, root_id, port).await?;
+                process_file(
+                    path_buf, parent_id, path.parent() == Some(root_path), port, index_types, file_types, tx, None, None,
+                    Arc::new(tokio::sync::Semaphore::new(DEFAULT_INGEST_CONCURRENCY)), DEFAULT_INGEST_CONCURRENCY, None,
+                ).await
+            } else {
+                Ok(())
+            }
+        }
+        EventKind::Modify(_) => {
+            if !path_buf.is_file() {
+                return Ok(());
+            }
+            let file_name = path.file_name().and_then(|s| s.to_str()).unwrap_or_default();
+            match lookup_file_id(path, root_path, root_id, port).await? {
+                Some(file_id) => update_file(
+                    path_buf, file_id, port, index_types, file_types, tx, Arc::new(OsFs), DEFAULT_INGEST_CONCURRENCY,
+                ).await,
+                None => {
+                    let parent_id = parent_folder_id(path, root_path, root_id, port).await?;
+                    println!("File {} modified before being indexed, ingesting it now", file_name);
+                    process_file(
+                        path_buf, parent_id, path.parent() == Some(root_path), port, index_types, file_types, tx, None, None,
+                        Arc::new(tokio::sync::Semaphore::new(DEFAULT_INGEST_CONCURRENCY)), DEFAULT_INGEST_CONCURRENCY, None,
+                    ).await
+                }
+            }
+        }
+        EventKind::Remove(_) => {
+            match lookup_file_id(path, root_path, root_id, port).await? {
+                Some(file_id) => {
+                    let file_name = path.file_name().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+                    let mut ids = HashMap::new();
+                    ids.insert(file_name.clone(), (file_id, String::new()));
+                    delete_files(vec![file_name], ids, port).await
+                }
+                None => {
+                    if let Some(folder_id) = lookup_folder_id(path, root_path, root_id, port).await? {
+                        delete_folder(folder_id, port).await
+                    } else {
+                        Ok(())
+                    }
+                }
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+async fn parent_folder_id(path: &Path, root_path: &Path, root_id: &str, port: u16) -> Result<String> {
+    if path.parent() == Some(root_path) {
+        return Ok(root_id.to_string());
+    }
+    lookup_folder_id(path.parent().unwrap_or(root_path), root_path, root_id, port)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Parent folder for {} is not indexed yet", path.display()))
+}
+
+async fn lookup_file_id(path: &Path, root_path: &Path, root_id: &str, port: u16) -> Result<Option<String>> {
+    let file_name = match path.file_name().and_then(|s| s.to_str()) {
+        Some(name) => name,
+        None => return Ok(None),
+    };
+    let ids = match path.parent() {
+        Some(parent) if parent == root_path => get_root_files(root_id.to_string(), port).await?,
+        Some(parent) => match lookup_folder_id(parent, root_path, root_id, port).await? {
+            Some(folder_id) => get_folder_files(folder_id, port).await?,
+            None => return Ok(None),
+        },
+        None => return Ok(None),
+    };
+    Ok(ids.get(file_name).map(|(id, _)| id.clone()))
+}
+
+async fn lookup_folder_id(path: &Path, root_path: &Path, root_id: &str, port: u16) -> Result<Option<String>> {
+    let folder_name = match path.file_name().and_then(|s| s.to_str()) {
+        Some(name) => name,
+        None => return Ok(None),
+    };
+    if path.parent() == Some(root_path) {
+        let root_folders = crate::queries::get_root_folders(root_id.to_string(), port).await?;
+        return Ok(root_folders.get(folder_name).cloned());
+    }
+    if path == root_path {
+        return Ok(None);
+    }
+    // Nested folders are resolved by walking down from the root, one
+    // `get_sub_folders` call per path component.
+    crate::updater::resolve_folder_id(&root_path.to_path_buf(), root_id, path, port).await
+}
+
+/// Builds a `.gitignore`-aware matcher so events for ignored paths (build
+/// output, `.git/`, etc.) are dropped before they ever reach `dispatch_event`.
+fn build_ignore_matcher(root_path: &Path) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(root_path);
+    builder.add(root_path.join(".gitignore"));
+    let _ = builder.add_line(None, ".git/");
+    builder.build().unwrap_or_else(|_| Gitignore::empty())
+}
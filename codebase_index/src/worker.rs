@@ -0,0 +1,101 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::utils::{COMPLETED_EMBEDDINGS, PENDING_EMBEDDINGS};
+
+/// Coarse state of the background embedding worker, as reported to the REPL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkerState {
+    /// Running, with embeddings currently in flight.
+    Active,
+    /// Running, but the queue is currently empty.
+    Idle,
+    /// Running, but not pulling new jobs off the channel.
+    Paused,
+    /// The worker task has exited.
+    Dead,
+}
+
+/// Shared control flags between the REPL and the background embedding
+/// worker. Cloning a `WorkerHandle` gives another view onto the same worker;
+/// the embedding task holds one end and checks it between batches, the REPL
+/// holds the other and calls `pause`/`resume`/`cancel` from user input.
+#[derive(Clone)]
+pub struct WorkerHandle {
+    paused: Arc<AtomicBool>,
+    cancelled: Arc<AtomicBool>,
+    alive: Arc<AtomicBool>,
+    abandoned: Arc<AtomicUsize>,
+}
+
+impl WorkerHandle {
+    pub fn new() -> Self {
+        Self {
+            paused: Arc::new(AtomicBool::new(false)),
+            cancelled: Arc::new(AtomicBool::new(false)),
+            alive: Arc::new(AtomicBool::new(true)),
+            abandoned: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Stops the worker from pulling new jobs off the channel. Anything
+    /// already queued stays queued until `resume` or `cancel`.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    /// Tells the worker to drain and discard whatever is left in the queue,
+    /// then exit. The number abandoned is available via `abandoned_count`
+    /// once `state()` reports `WorkerState::Dead`.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    pub fn abandoned_count(&self) -> usize {
+        self.abandoned.load(Ordering::SeqCst)
+    }
+
+    pub fn record_abandoned(&self, count: usize) {
+        self.abandoned.fetch_add(count, Ordering::SeqCst);
+    }
+
+    pub fn mark_dead(&self) {
+        self.alive.store(false, Ordering::SeqCst);
+    }
+
+    /// Current worker state, combining the control flags above with the
+    /// global pending/completed embedding counters.
+    pub fn state(&self) -> WorkerState {
+        if !self.alive.load(Ordering::SeqCst) {
+            return WorkerState::Dead;
+        }
+        if self.paused.load(Ordering::SeqCst) {
+            return WorkerState::Paused;
+        }
+        if PENDING_EMBEDDINGS.load(Ordering::SeqCst) > COMPLETED_EMBEDDINGS.load(Ordering::SeqCst) {
+            WorkerState::Active
+        } else {
+            WorkerState::Idle
+        }
+    }
+
+    /// Snapshot of (pending, completed) embedding counts for progress display.
+    pub fn counts(&self) -> (usize, usize) {
+        (
+            PENDING_EMBEDDINGS.load(Ordering::SeqCst),
+            COMPLETED_EMBEDDINGS.load(Ordering::SeqCst),
+        )
+    }
+}
@@ -58,7 +58,6 @@ use helix_db::{
         response::Response,
         return_values::ReturnValue,
         value::Value,
-        format::Format,
     },
     utils::{
         count::Count,
@@ -68,15 +67,20 @@ use helix_db::{
     },
 };
 use sonic_rs::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
-use std::sync::Arc;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
     
 pub fn config() -> Option<Config> {return Some(Config {vector_config: Some(VectorConfig {m: Some(16),ef_construction: Some(768),ef_search: Some(768),}),graph_config: Some(GraphConfig {secondary_indices: Some(vec![]),}),db_max_size_gb: Some(10),mcp: Some(true),bm25: Some(true),schema: None,embedding_model: None,graphvis_node_label: None,})}
 pub struct Root {
     pub name: String,
     pub extracted_at: DateTime<Utc>,
+    pub commit_sha: String,
 }
 
 pub struct Folder {
@@ -87,8 +91,10 @@ pub struct Folder {
 pub struct File {
     pub name: String,
     pub extension: String,
-    pub text: String,
+    pub text_hash: String,
+    pub text_backend: String,
     pub extracted_at: DateTime<Utc>,
+    pub revision: i64,
 }
 
 pub struct Entity {
@@ -98,6 +104,8 @@ pub struct Entity {
     pub order: i64,
     pub text: String,
     pub extracted_at: DateTime<Utc>,
+    pub source_revision: i64,
+    pub content_hash: String,
 }
 
 pub struct Root_to_Folder {
@@ -139,6 +147,529 @@ pub struct EmbededCode {
     pub vector: Vec<f64>,
 }
 
+/// Levenshtein automaton for typo-tolerant name matching, built once per
+/// query and then fed every candidate name character by character.
+///
+/// A "state" is the NFA's live position set collapsed to its determinized
+/// form: a row of the minimum edit count reachable at each prefix position
+/// of `query`, exactly as the standard Levenshtein-NFA subset construction
+/// produces. `step` computes a state's transition for a given input
+/// character and memoizes it in `transitions`, so candidates that share a
+/// prefix (or just recur across a directory listing) reuse already-computed
+/// states instead of recomputing them — the lazy determinization the naive
+/// per-candidate DP this replaced didn't share anything between candidates.
+struct LevenshteinAutomaton<'q> {
+    query: &'q [char],
+    max_distance: usize,
+    transitions: RefCell<HashMap<(Vec<usize>, char), Rc<Vec<usize>>>>,
+}
+
+impl<'q> LevenshteinAutomaton<'q> {
+    fn new(query: &'q [char], max_distance: usize) -> Self {
+        LevenshteinAutomaton { query, max_distance, transitions: RefCell::new(HashMap::new()) }
+    }
+
+    fn start(&self) -> Rc<Vec<usize>> {
+        Rc::new((0..=self.query.len()).collect())
+    }
+
+    /// Determinized transition out of `state` on `c`, computed once and
+    /// cached for every later candidate that reaches this same state.
+    fn step(&self, state: &Rc<Vec<usize>>, c: char) -> Option<Rc<Vec<usize>>> {
+        let key = (state.as_ref().clone(), c);
+        if let Some(cached) = self.transitions.borrow().get(&key) {
+            return Some(Rc::clone(cached));
+        }
+        let n = self.query.len();
+        let mut next_row = Vec::with_capacity(n + 1);
+        next_row.push(state[0] + 1);
+        for j in 0..n {
+            let substitution = state[j] + if self.query[j] == c { 0 } else { 1 };
+            let deletion = state[j + 1] + 1;
+            let insertion = next_row[j] + 1;
+            next_row.push(substitution.min(deletion).min(insertion));
+        }
+        if *next_row.iter().min().unwrap() > self.max_distance {
+            return None;
+        }
+        let next_state = Rc::new(next_row);
+        self.transitions.borrow_mut().insert(key, Rc::clone(&next_state));
+        Some(next_state)
+    }
+
+    /// Feeds `candidate` through the automaton one character at a time,
+    /// bailing out as soon as every live state exceeds `max_distance`.
+    fn distance(&self, candidate: &str) -> Option<usize> {
+        let mut state = self.start();
+        for c in candidate.chars() {
+            state = self.step(&state, c)?;
+        }
+        let distance = state[self.query.len()];
+        if distance <= self.max_distance { Some(distance) } else { None }
+    }
+}
+
+/// Cheap, dependency-free content hash used for entity staleness tracking
+/// (`getStaleEntities`): FNV-1a over the UTF-8 bytes, rendered as lowercase
+/// hex. Not cryptographic, just needs to change whenever `File.text` does.
+fn content_hash(text: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in text.as_bytes() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Parses a `vector` node property back into its `Vec<f64>` components for
+/// `searchSimilarEntities`' cosine scoring. Like every other scalar pulled
+/// out of a property `Value` in this file, this goes through `to_string()`;
+/// the stored `Vec<f64>` renders as a bracketed, comma-separated list
+/// (e.g. `[0.1, 0.2, 0.3]`), which is stripped and split here.
+fn parse_vector_property(s: &str) -> Vec<f64> {
+    s.trim_matches(|c| c == '[' || c == ']')
+        .split(',')
+        .filter_map(|part| part.trim().parse::<f64>().ok())
+        .collect()
+}
+
+/// `dot(a, b) / (||a|| * ||b||)`, 0 if either vector is all zeros.
+fn cosine_similarity(a: &[f64], b: &[f64]) -> f64 {
+    let dot: f64 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
+    let norm_a: f64 = a.iter().map(|x| x * x).sum::<f64>().sqrt();
+    let norm_b: f64 = b.iter().map(|x| x * x).sum::<f64>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 { 0.0 } else { dot / (norm_a * norm_b) }
+}
+
+/// Content-addressed blob storage for `File.text`. Several read handlers
+/// (`getFile`, `getRootFiles`, `getFileByExtension`, ...) used to
+/// `exclude_field!` a `text` property that was inlined on every `File`
+/// node purely to keep traversals/serialization cheap; storing the body
+/// out-of-band and keeping only its hash + backend id on the node gets the
+/// same win without the special-casing. `put` stores `bytes` under the
+/// `ContentHash` it hashes them to (storing twice under the same hash is a
+/// no-op); `get` dereferences a previously-stored hash back to bytes, or
+/// `None` if this backend never saw it.
+///
+/// `ContentHash` is a blake3 digest, not `content_hash`'s FNV-1a (that one
+/// stays put for `getStaleEntities`' staleness check, where a cheap, possibly
+/// colliding hash is fine). Addressing, unlike staleness, needs collisions to
+/// actually not happen: a 64-bit FNV collision here would silently serve one
+/// file's content in place of another's.
+pub type ContentHash = String;
+
+fn blob_hash(bytes: &[u8]) -> ContentHash {
+    blake3::hash(bytes).to_hex().to_string()
+}
+
+pub trait BlobStore: Send + Sync {
+    fn put(&self, bytes: &[u8]) -> ContentHash;
+    fn get(&self, hash: &ContentHash) -> Option<Vec<u8>>;
+    fn backend_id(&self) -> &'static str;
+}
+
+/// Process-local backend: convenient for tests, but every blob it holds is
+/// lost on restart. Not what `BLOB_STORE` is wired up to; kept around as the
+/// cheap option a test can construct directly instead of touching disk.
+pub struct InMemoryBlobStore {
+    blobs: Mutex<HashMap<ContentHash, Vec<u8>>>,
+}
+
+impl InMemoryBlobStore {
+    pub fn new() -> Self {
+        InMemoryBlobStore { blobs: Mutex::new(HashMap::new()) }
+    }
+}
+
+impl BlobStore for InMemoryBlobStore {
+    fn put(&self, bytes: &[u8]) -> ContentHash {
+        let hash = blob_hash(bytes);
+        self.blobs.lock().unwrap().entry(hash.clone()).or_insert_with(|| bytes.to_vec());
+        hash
+    }
+
+    fn get(&self, hash: &ContentHash) -> Option<Vec<u8>> {
+        self.blobs.lock().unwrap().get(hash).cloned()
+    }
+
+    fn backend_id(&self) -> &'static str {
+        "memory"
+    }
+}
+
+/// Disk-backed default: each blob is a plain file named by its `ContentHash`
+/// under `root` (override via the `MEDKIT_BLOB_DIR` env var; defaults to
+/// `./blob_store`), so `File.text` survives a process restart instead of
+/// living only in a `HashMap` — the persisted graph already references every
+/// blob by this same hash, so losing the backing store on restart would
+/// otherwise silently orphan it. `put` is a no-op if the file already exists,
+/// same contract as `InMemoryBlobStore`.
+pub struct FsBlobStore {
+    root: PathBuf,
+}
+
+impl FsBlobStore {
+    pub fn new(root: PathBuf) -> Self {
+        if let Err(e) = std::fs::create_dir_all(&root) {
+            eprintln!("FsBlobStore: failed to create blob dir {}: {}", root.display(), e);
+        }
+        FsBlobStore { root }
+    }
+
+    fn path_for(&self, hash: &ContentHash) -> PathBuf {
+        self.root.join(hash)
+    }
+}
+
+impl BlobStore for FsBlobStore {
+    fn put(&self, bytes: &[u8]) -> ContentHash {
+        let hash = blob_hash(bytes);
+        let path = self.path_for(&hash);
+        if !path.exists() {
+            if let Err(e) = std::fs::write(&path, bytes) {
+                eprintln!("FsBlobStore: failed to persist blob {}: {}", hash, e);
+            }
+        }
+        hash
+    }
+
+    fn get(&self, hash: &ContentHash) -> Option<Vec<u8>> {
+        std::fs::read(self.path_for(hash)).ok()
+    }
+
+    fn backend_id(&self) -> &'static str {
+        "fs"
+    }
+}
+
+lazy_static! {
+    /// Backing store for `File.text`. `FsBlobStore`, rooted at `MEDKIT_BLOB_DIR`
+    /// (default `./blob_store`) so content survives a restart; swap for an
+    /// object-store-backed `BlobStore` impl if/when one is needed.
+    static ref BLOB_STORE: FsBlobStore = FsBlobStore::new(
+        std::env::var("MEDKIT_BLOB_DIR").map(PathBuf::from).unwrap_or_else(|_| PathBuf::from("blob_store"))
+    );
+}
+
+/// Resolves a tree-sitter grammar by language name for `ingestFile`. Mirrors
+/// the extension-based table the ingestion client uses, keyed by name
+/// instead of extension since `ingestFile` already knows the language.
+fn get_ts_language(language: &str) -> Option<tree_sitter::Language> {
+    match language {
+        "python" | "py" => Some(tree_sitter_python::LANGUAGE.into()),
+        "rust" | "rs" => Some(tree_sitter_rust::LANGUAGE.into()),
+        "zig" => Some(tree_sitter_zig::LANGUAGE.into()),
+        "cpp" | "cc" | "cxx" => Some(tree_sitter_cpp::LANGUAGE.into()),
+        "c" | "h" => Some(tree_sitter_c::LANGUAGE.into()),
+        "typescript" | "ts" | "mts" | "cts" => Some(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into()),
+        "tsx" => Some(tree_sitter_typescript::LANGUAGE_TSX.into()),
+        "javascript" | "js" | "jsx" | "mjs" | "cjs" => Some(tree_sitter_javascript::LANGUAGE.into()),
+        _ => None,
+    }
+}
+
+/// A value a predicate-DSL expression can produce: either a constant
+/// literal or the coerced reading of a node property.
+#[derive(Debug, Clone)]
+enum FilterValue {
+    Num(f64),
+    Str(String),
+    Bool(bool),
+}
+
+/// AST for the `queryEntities` filter-expression DSL: a constant, a
+/// property identifier to resolve against the current node, or a binary
+/// operator over two sub-expressions (`== != < <= > >= && || + - * /`).
+#[derive(Debug, Clone)]
+enum FilterExpr {
+    Const(FilterValue),
+    Ident(String),
+    BinOp(&'static str, Box<FilterExpr>, Box<FilterExpr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum FilterToken {
+    Num(f64),
+    Str(String),
+    Bool(bool),
+    Ident(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+}
+
+fn tokenize_filter_expr(src: &str) -> Option<Vec<FilterToken>> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        match c {
+            '(' => { tokens.push(FilterToken::LParen); i += 1; }
+            ')' => { tokens.push(FilterToken::RParen); i += 1; }
+            '"' => {
+                i += 1;
+                let start = i;
+                while i < chars.len() && chars[i] != '"' { i += 1; }
+                tokens.push(FilterToken::Str(chars[start..i].iter().collect()));
+                i += 1;
+            }
+            '=' if chars.get(i + 1) == Some(&'=') => { tokens.push(FilterToken::Op("==")); i += 2; }
+            '!' if chars.get(i + 1) == Some(&'=') => { tokens.push(FilterToken::Op("!=")); i += 2; }
+            '<' if chars.get(i + 1) == Some(&'=') => { tokens.push(FilterToken::Op("<=")); i += 2; }
+            '>' if chars.get(i + 1) == Some(&'=') => { tokens.push(FilterToken::Op(">=")); i += 2; }
+            '&' if chars.get(i + 1) == Some(&'&') => { tokens.push(FilterToken::Op("&&")); i += 2; }
+            '|' if chars.get(i + 1) == Some(&'|') => { tokens.push(FilterToken::Op("||")); i += 2; }
+            '<' => { tokens.push(FilterToken::Op("<")); i += 1; }
+            '>' => { tokens.push(FilterToken::Op(">")); i += 1; }
+            '+' => { tokens.push(FilterToken::Op("+")); i += 1; }
+            '-' => { tokens.push(FilterToken::Op("-")); i += 1; }
+            '*' => { tokens.push(FilterToken::Op("*")); i += 1; }
+            '/' => { tokens.push(FilterToken::Op("/")); i += 1; }
+            c if c.is_ascii_digit() => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') { i += 1; }
+                let text: String = chars[start..i].iter().collect();
+                tokens.push(FilterToken::Num(text.parse().ok()?));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') { i += 1; }
+                let ident: String = chars[start..i].iter().collect();
+                match ident.as_str() {
+                    "true" => tokens.push(FilterToken::Bool(true)),
+                    "false" => tokens.push(FilterToken::Bool(false)),
+                    _ => tokens.push(FilterToken::Ident(ident)),
+                }
+            }
+            _ => return None,
+        }
+    }
+    Some(tokens)
+}
+
+/// Binding powers for precedence-climbing: `||` lowest, then `&&`, then
+/// the comparisons, then `+`/`-`, then `*`/`/` highest.
+fn binding_power(op: &str) -> Option<(u8, u8)> {
+    match op {
+        "||" => Some((1, 2)),
+        "&&" => Some((3, 4)),
+        "==" | "!=" | "<" | "<=" | ">" | ">=" => Some((5, 6)),
+        "+" | "-" => Some((7, 8)),
+        "*" | "/" => Some((9, 10)),
+        _ => None,
+    }
+}
+
+struct FilterParser {
+    tokens: Vec<FilterToken>,
+    pos: usize,
+}
+
+impl FilterParser {
+    fn peek(&self) -> Option<&FilterToken> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<FilterToken> {
+        let tok = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        tok
+    }
+
+    fn parse_expr(&mut self, min_bp: u8) -> Option<FilterExpr> {
+        let mut lhs = self.parse_primary()?;
+        loop {
+            let op = match self.peek() {
+                Some(FilterToken::Op(op)) => *op,
+                _ => break,
+            };
+            let (left_bp, right_bp) = binding_power(op)?;
+            if left_bp < min_bp {
+                break;
+            }
+            self.advance();
+            let rhs = self.parse_expr(right_bp)?;
+            lhs = FilterExpr::BinOp(op, Box::new(lhs), Box::new(rhs));
+        }
+        Some(lhs)
+    }
+
+    fn parse_primary(&mut self) -> Option<FilterExpr> {
+        match self.advance()? {
+            FilterToken::Num(n) => Some(FilterExpr::Const(FilterValue::Num(n))),
+            FilterToken::Str(s) => Some(FilterExpr::Const(FilterValue::Str(s))),
+            FilterToken::Bool(b) => Some(FilterExpr::Const(FilterValue::Bool(b))),
+            FilterToken::Ident(name) => Some(FilterExpr::Ident(name)),
+            FilterToken::LParen => {
+                let inner = self.parse_expr(0)?;
+                match self.advance() {
+                    Some(FilterToken::RParen) => Some(inner),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Parses a `queryEntities` filter expression, or `None` on any malformed
+/// input (unexpected token, unmatched paren, or trailing garbage) — the
+/// handler reports no matches for a filter that fails to parse; only an
+/// unknown identifier in a filter that does parse is reported as a
+/// `GraphError` (see `eval_filter_expr`).
+fn parse_filter_expr(src: &str) -> Option<FilterExpr> {
+    let tokens = tokenize_filter_expr(src)?;
+    let mut parser = FilterParser { tokens, pos: 0 };
+    let expr = parser.parse_expr(0)?;
+    if parser.pos == parser.tokens.len() { Some(expr) } else { None }
+}
+
+fn apply_filter_op(op: &str, lhs: FilterValue, rhs: FilterValue) -> FilterValue {
+    use FilterValue::{Bool, Num, Str};
+    match (op, lhs, rhs) {
+        ("+", Num(a), Num(b)) => Num(a + b),
+        ("-", Num(a), Num(b)) => Num(a - b),
+        ("*", Num(a), Num(b)) => Num(a * b),
+        ("/", Num(a), Num(b)) => Num(a / b),
+        ("==", Num(a), Num(b)) => Bool(a == b),
+        ("==", Str(a), Str(b)) => Bool(a == b),
+        ("==", Bool(a), Bool(b)) => Bool(a == b),
+        ("!=", Num(a), Num(b)) => Bool(a != b),
+        ("!=", Str(a), Str(b)) => Bool(a != b),
+        ("!=", Bool(a), Bool(b)) => Bool(a != b),
+        ("<", Num(a), Num(b)) => Bool(a < b),
+        ("<=", Num(a), Num(b)) => Bool(a <= b),
+        (">", Num(a), Num(b)) => Bool(a > b),
+        (">=", Num(a), Num(b)) => Bool(a >= b),
+        ("<", Str(a), Str(b)) => Bool(a < b),
+        ("<=", Str(a), Str(b)) => Bool(a <= b),
+        (">", Str(a), Str(b)) => Bool(a > b),
+        (">=", Str(a), Str(b)) => Bool(a >= b),
+        ("&&", Bool(a), Bool(b)) => Bool(a && b),
+        ("||", Bool(a), Bool(b)) => Bool(a || b),
+        _ => Bool(false),
+    }
+}
+
+/// Evaluates a parsed filter expression against the current node, resolving
+/// `Ident` through `resolve` (a `check_property` lookup coerced into a
+/// `FilterValue`, or a `GraphError` if the identifier isn't a property on
+/// this node at all). Mismatched operand *types* still fall back to
+/// `Bool(false)` — `entity_type == 1` simply doesn't match — but an unknown
+/// identifier is a query bug, not a non-match, so it propagates as an error
+/// instead of silently coercing to `false`.
+fn eval_filter_expr(
+    expr: &FilterExpr,
+    resolve: &mut impl FnMut(&str) -> Result<FilterValue, GraphError>,
+) -> Result<FilterValue, GraphError> {
+    match expr {
+        FilterExpr::Const(v) => Ok(v.clone()),
+        FilterExpr::Ident(name) => resolve(name),
+        FilterExpr::BinOp(op, lhs, rhs) => {
+            let lhs = eval_filter_expr(lhs, resolve)?;
+            let rhs = eval_filter_expr(rhs, resolve)?;
+            Ok(apply_filter_op(op, lhs, rhs))
+        }
+    }
+}
+
+/// Coerces a node property `Value` into a `FilterValue`: numeric text
+/// parses as `Num`, `"true"`/`"false"` as `Bool`, anything else as `Str`.
+fn filter_value_from_property(v: &Value) -> FilterValue {
+    let s = v.to_string();
+    if s == "true" {
+        FilterValue::Bool(true)
+    } else if s == "false" {
+        FilterValue::Bool(false)
+    } else if let Ok(n) = s.parse::<f64>() {
+        FilterValue::Num(n)
+    } else {
+        FilterValue::Str(s)
+    }
+}
+
+lazy_static! {
+    /// Inverted index for `searchFiles`: token -> file id -> term frequency
+    /// in that file. Patched incrementally by `createFile`/`createSuperFile`
+    /// and `updateFile` within a process's lifetime, but carries nothing
+    /// across a restart — `searchFiles` rebuilds it from the persisted
+    /// `File` nodes the first time it runs in a fresh process (see
+    /// `BM25_BUILT` below) rather than trusting it's already warm.
+    static ref BM25_INDEX: Mutex<HashMap<String, HashMap<ID, usize>>> = Mutex::new(HashMap::new());
+    /// file id -> token count, used for BM25's length-normalization term.
+    static ref BM25_DOC_LENGTHS: Mutex<HashMap<ID, usize>> = Mutex::new(HashMap::new());
+}
+
+/// Guards the one-time rebuild of `BM25_INDEX`/`BM25_DOC_LENGTHS` from the
+/// graph's persisted `File` nodes. `searchFiles` flips this on its first
+/// call in a process; every call after that trusts the in-memory index,
+/// same as before.
+static BM25_BUILT: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Tokenizes on lowercased unicode word boundaries: runs of alphanumeric
+/// characters become tokens, everything else is a separator.
+fn tokenize_bm25(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            current.extend(c.to_lowercase());
+        } else if !current.is_empty() {
+            tokens.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// (Re-)indexes a file's text for `searchFiles`, replacing whatever was
+/// indexed for it before. Called from file creation and `updateFile` so the
+/// index tracks the graph instead of drifting from it.
+fn bm25_index_file(file_id: ID, text: &str) {
+    let tokens = tokenize_bm25(text);
+    let mut index = BM25_INDEX.lock().unwrap();
+    for postings in index.values_mut() {
+        postings.remove(&file_id);
+    }
+    let mut term_freqs: HashMap<String, usize> = HashMap::new();
+    for token in &tokens {
+        *term_freqs.entry(token.clone()).or_insert(0) += 1;
+    }
+    for (token, freq) in term_freqs {
+        index.entry(token).or_insert_with(HashMap::new).insert(file_id.clone(), freq);
+    }
+    BM25_DOC_LENGTHS.lock().unwrap().insert(file_id, tokens.len());
+}
+
+/// Removes a deleted file's postings from the `searchFiles` index.
+fn bm25_remove_file(file_id: &ID) {
+    let mut index = BM25_INDEX.lock().unwrap();
+    for postings in index.values_mut() {
+        postings.remove(file_id);
+    }
+    BM25_DOC_LENGTHS.lock().unwrap().remove(file_id);
+}
+
+/// Tree-sitter node kinds `ingestFile` indexes as `Entity` nodes — the
+/// declaration-shaped nodes (functions, types, modules) worth surfacing in
+/// the graph, across every grammar `get_ts_language` supports. Everything
+/// else is walked through but not turned into an `Entity`.
+fn is_entity_kind(kind: &str) -> bool {
+    matches!(
+        kind,
+        "function_item" | "struct_item" | "enum_item" | "impl_item" | "trait_item" | "mod_item"
+            | "function_definition" | "class_definition"
+            | "function_declaration" | "class_declaration" | "method_definition" | "interface_declaration"
+    )
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct getFileRootInput {
 
@@ -167,10 +698,51 @@ pub fn getFileContent (input: &HandlerInput) -> Result<Response, GraphError> {
 {
     let file = G::new(Arc::clone(&db), &txn)
 .n_from_id(&data.file_id).collect_to_obj();
+    let text_hash: String = G::new_from(Arc::clone(&db), &txn, vec![file.clone()])
+.check_property("text_hash")
+.map_value_or(String::new(), |v| v.to_string())?;
+    let text = BLOB_STORE.get(&text_hash).map(|bytes| String::from_utf8_lossy(&bytes).into_owned()).unwrap_or_default();
 let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
-        return_vals.insert("file".to_string(), ReturnValue::from_traversal_value_with_mixin(G::new_from(Arc::clone(&db), &txn, file.clone())
+        return_vals.insert("text".to_string(), ReturnValue::from(Value::from(text)));
+
+}
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct getFileTextInput {
+
+pub file_id: ID
+}
+#[handler(with_read)]
+pub fn getFileText (input: &HandlerInput) -> Result<Response, GraphError> {
+{
+    let file = G::new(Arc::clone(&db), &txn)
+.n_from_id(&data.file_id).collect_to_obj();
+    let text_hash: String = G::new_from(Arc::clone(&db), &txn, vec![file.clone()])
+.check_property("text_hash")
+.map_value_or(String::new(), |v| v.to_string())?;
+    let text = BLOB_STORE.get(&text_hash).map(|bytes| String::from_utf8_lossy(&bytes).into_owned()).unwrap_or_default();
+let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
+        return_vals.insert("text".to_string(), ReturnValue::from(Value::from(text)));
+
+}
+}
 
-.check_property("text").collect_to_obj().clone(), remapping_vals.borrow_mut()));
+#[derive(Serialize, Deserialize)]
+pub struct getFileHashInput {
+
+pub file_id: ID
+}
+#[handler(with_read)]
+pub fn getFileHash (input: &HandlerInput) -> Result<Response, GraphError> {
+{
+    let file = G::new(Arc::clone(&db), &txn)
+.n_from_id(&data.file_id).collect_to_obj();
+    let hash: String = G::new_from(Arc::clone(&db), &txn, vec![file.clone()])
+.check_property("text_hash")
+.map_value_or(String::new(), |v| v.to_string())?;
+let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
+        return_vals.insert("hash".to_string(), ReturnValue::from(Value::from(hash)));
 
 }
 }
@@ -188,10 +760,13 @@ pub fn createSuperFile (input: &HandlerInput) -> Result<Response, GraphError> {
 {
     let root = G::new(Arc::clone(&db), &txn)
 .n_from_id(&data.root_id).collect_to_obj();
+    let text_hash = BLOB_STORE.put(data.text.as_bytes());
+    let text_backend = BLOB_STORE.backend_id().to_string();
     let file = G::new_mut(Arc::clone(&db), &mut txn)
-.add_n("File", Some(props! { "name" => &data.name, "extracted_at" => chrono::Utc::now().to_rfc3339(), "text" => &data.text, "extension" => &data.extension }), None).collect_to_obj();
+.add_n("File", Some(props! { "name" => &data.name, "extracted_at" => chrono::Utc::now().to_rfc3339(), "text_hash" => &text_hash, "text_backend" => &text_backend, "extension" => &data.extension, "revision" => &1i64 }), None).collect_to_obj();
     G::new_mut(Arc::clone(&db), &mut txn)
 .add_e("Root_to_File", None, root.id(), file.id(), true, EdgeType::Node).collect_to_obj();
+    bm25_index_file(file.id(), &data.text);
 let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
         return_vals.insert("file".to_string(), ReturnValue::from_traversal_value_with_mixin(file.clone().clone(), remapping_vals.borrow_mut()));
 
@@ -211,10 +786,7 @@ pub fn getEntityFile (input: &HandlerInput) -> Result<Response, GraphError> {
 
 .in_("File_to_Entity",&EdgeType::Node).collect_to::<Vec<_>>();
 let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
-        return_vals.insert("file".to_string(), ReturnValue::from_traversal_value_array_with_mixin(G::new_from(Arc::clone(&db), &txn, file.clone())
-
-.map_traversal(|item, txn| { exclude_field!(remapping_vals, item.clone(), "text")?;
- Ok(item) }).collect_to::<Vec<_>>().clone(), remapping_vals.borrow_mut()));
+        return_vals.insert("file".to_string(), ReturnValue::from_traversal_value_array_with_mixin(file.clone().clone(), remapping_vals.borrow_mut()));
 
 }
 }
@@ -250,10 +822,7 @@ pub fn getFolderFiles (input: &HandlerInput) -> Result<Response, GraphError> {
 
 .out("Folder_to_File",&EdgeType::Node).collect_to::<Vec<_>>();
 let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
-        return_vals.insert("files".to_string(), ReturnValue::from_traversal_value_array_with_mixin(G::new_from(Arc::clone(&db), &txn, files.clone())
-
-.map_traversal(|item, txn| { exclude_field!(remapping_vals, item.clone(), "text")?;
- Ok(item) }).collect_to::<Vec<_>>().clone(), remapping_vals.borrow_mut()));
+        return_vals.insert("files".to_string(), ReturnValue::from_traversal_value_array_with_mixin(files.clone().clone(), remapping_vals.borrow_mut()));
 
 }
 }
@@ -360,10 +929,46 @@ pub fn getFileByName (input: &HandlerInput) -> Result<Response, GraphError> {
                 }
             }).collect_to::<Vec<_>>();
 let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
-        return_vals.insert("file".to_string(), ReturnValue::from_traversal_value_array_with_mixin(G::new_from(Arc::clone(&db), &txn, file.clone())
+        return_vals.insert("file".to_string(), ReturnValue::from_traversal_value_array_with_mixin(file.clone().clone(), remapping_vals.borrow_mut()));
 
-.map_traversal(|item, txn| { exclude_field!(remapping_vals, item.clone(), "text")?;
- Ok(item) }).collect_to::<Vec<_>>().clone(), remapping_vals.borrow_mut()));
+}
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct getFileByNameFuzzyInput {
+
+pub name: String,
+pub max_distance: i64
+}
+#[handler(with_read)]
+pub fn getFileByNameFuzzy (input: &HandlerInput) -> Result<Response, GraphError> {
+{
+    let query_chars: Vec<char> = data.name.chars().collect();
+    let max_distance = data.max_distance as usize;
+    let automaton = LevenshteinAutomaton::new(&query_chars, max_distance);
+    let distances: std::cell::RefCell<HashMap<ID, i64>> = std::cell::RefCell::new(HashMap::new());
+    let mut file = G::new(Arc::clone(&db), &txn)
+.n_from_type("File")
+
+.filter_ref(|val, txn|{
+                if let Ok(val) = val {
+                    Ok(G::new_from(Arc::clone(&db), &txn, val.clone())
+
+.check_property("name")
+
+.map_value_or(false, |v| {
+                match automaton.distance(&v.to_string()) {
+                    Some(distance) => { distances.borrow_mut().insert(val.id(), distance as i64); true }
+                    None => false,
+                }
+            })?)
+                } else {
+                    Ok(false)
+                }
+            }).collect_to::<Vec<_>>();
+    file.sort_by_key(|v| *distances.borrow().get(&v.id()).unwrap_or(&i64::MAX));
+let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
+        return_vals.insert("file".to_string(), ReturnValue::from_traversal_value_array_with_mixin(file.clone().clone(), remapping_vals.borrow_mut()));
 
 }
 }
@@ -396,6 +1001,120 @@ let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
 }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct getFolderByPathInput {
+
+pub root_id: ID,
+pub path: String
+}
+#[handler(with_read)]
+pub fn getFolderByPath (input: &HandlerInput) -> Result<Response, GraphError> {
+{
+    let segments: Vec<String> = data.path.split('/').filter(|s| !s.is_empty()).map(|s| s.to_string()).collect();
+    let mut current_id = data.root_id.clone();
+    let mut is_root = true;
+    let mut resolved: Vec<TraversalVal> = Vec::new();
+    let mut ambiguous = false;
+    for segment in &segments {
+        let edge_label = if is_root { "Root_to_Folder" } else { "Folder_to_Folder" };
+        let matches = G::new(Arc::clone(&db), &txn)
+.n_from_id(&current_id)
+
+.out(edge_label,&EdgeType::Node)
+
+.filter_ref(|val, txn|{
+                if let Ok(val) = val {
+                    Ok(G::new_from(Arc::clone(&db), &txn, val.clone())
+
+.check_property("name")
+
+.map_value_or(false, |v| *v == segment.clone())?)
+                } else {
+                    Ok(false)
+                }
+            }).collect_to::<Vec<_>>();
+        if matches.len() > 1 { ambiguous = true; resolved = Vec::new(); break; }
+        if matches.is_empty() { resolved = Vec::new(); break; }
+        current_id = matches[0].id();
+        resolved = vec![matches[0].clone()];
+        is_root = false;
+    }
+let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
+        return_vals.insert("folder".to_string(), ReturnValue::from_traversal_value_array_with_mixin(resolved.clone(), remapping_vals.borrow_mut()));
+        return_vals.insert("ambiguous".to_string(), ReturnValue::from(Value::from(ambiguous)));
+
+}
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct getFileByPathInput {
+
+pub root_id: ID,
+pub path: String
+}
+#[handler(with_read)]
+pub fn getFileByPath (input: &HandlerInput) -> Result<Response, GraphError> {
+{
+    let segments: Vec<String> = data.path.split('/').filter(|s| !s.is_empty()).map(|s| s.to_string()).collect();
+    let file_name = segments.last().cloned();
+    let folder_segments: Vec<String> = if segments.is_empty() { Vec::new() } else { segments[..segments.len() - 1].to_vec() };
+    let mut current_id = data.root_id.clone();
+    let mut is_root = true;
+    let mut ambiguous = false;
+    let mut parent_resolved = true;
+    for segment in &folder_segments {
+        let edge_label = if is_root { "Root_to_Folder" } else { "Folder_to_Folder" };
+        let matches = G::new(Arc::clone(&db), &txn)
+.n_from_id(&current_id)
+
+.out(edge_label,&EdgeType::Node)
+
+.filter_ref(|val, txn|{
+                if let Ok(val) = val {
+                    Ok(G::new_from(Arc::clone(&db), &txn, val.clone())
+
+.check_property("name")
+
+.map_value_or(false, |v| *v == segment.clone())?)
+                } else {
+                    Ok(false)
+                }
+            }).collect_to::<Vec<_>>();
+        if matches.len() > 1 { ambiguous = true; parent_resolved = false; break; }
+        if matches.is_empty() { parent_resolved = false; break; }
+        current_id = matches[0].id();
+        is_root = false;
+    }
+    let files: Vec<TraversalVal> = match (&file_name, parent_resolved) {
+        (Some(name), true) => {
+            let edge_label = if is_root { "Root_to_File" } else { "Folder_to_File" };
+            let matches = G::new(Arc::clone(&db), &txn)
+.n_from_id(&current_id)
+
+.out(edge_label,&EdgeType::Node)
+
+.filter_ref(|val, txn|{
+                if let Ok(val) = val {
+                    Ok(G::new_from(Arc::clone(&db), &txn, val.clone())
+
+.check_property("name")
+
+.map_value_or(false, |v| *v == name.clone())?)
+                } else {
+                    Ok(false)
+                }
+            }).collect_to::<Vec<_>>();
+            if matches.len() > 1 { ambiguous = true; Vec::new() } else { matches }
+        }
+        _ => Vec::new(),
+    };
+let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
+        return_vals.insert("file".to_string(), ReturnValue::from_traversal_value_array_with_mixin(files.clone(), remapping_vals.borrow_mut()));
+        return_vals.insert("ambiguous".to_string(), ReturnValue::from(Value::from(ambiguous)));
+
+}
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct getAllFilesInput {
 
@@ -407,10 +1126,7 @@ pub fn getAllFiles (input: &HandlerInput) -> Result<Response, GraphError> {
     let files = G::new(Arc::clone(&db), &txn)
 .n_from_type("File").collect_to::<Vec<_>>();
 let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
-        return_vals.insert("files".to_string(), ReturnValue::from_traversal_value_array_with_mixin(G::new_from(Arc::clone(&db), &txn, files.clone())
-
-.map_traversal(|item, txn| { exclude_field!(remapping_vals, item.clone(), "text")?;
- Ok(item) }).collect_to::<Vec<_>>().clone(), remapping_vals.borrow_mut()));
+        return_vals.insert("files".to_string(), ReturnValue::from_traversal_value_array_with_mixin(files.clone().clone(), remapping_vals.borrow_mut()));
 
 }
 }
@@ -449,10 +1165,13 @@ pub fn createFile (input: &HandlerInput) -> Result<Response, GraphError> {
 {
     let folder = G::new(Arc::clone(&db), &txn)
 .n_from_id(&data.folder_id).collect_to_obj();
+    let text_hash = BLOB_STORE.put(data.text.as_bytes());
+    let text_backend = BLOB_STORE.backend_id().to_string();
     let file = G::new_mut(Arc::clone(&db), &mut txn)
-.add_n("File", Some(props! { "text" => &data.text, "extension" => &data.extension, "name" => &data.name, "extracted_at" => chrono::Utc::now().to_rfc3339() }), None).collect_to_obj();
+.add_n("File", Some(props! { "text_hash" => &text_hash, "text_backend" => &text_backend, "extension" => &data.extension, "name" => &data.name, "extracted_at" => chrono::Utc::now().to_rfc3339(), "revision" => &1i64 }), None).collect_to_obj();
     G::new_mut(Arc::clone(&db), &mut txn)
 .add_e("Folder_to_File", None, folder.id(), file.id(), true, EdgeType::Node).collect_to_obj();
+    bm25_index_file(file.id(), &data.text);
 let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
         return_vals.insert("file".to_string(), ReturnValue::from_traversal_value_with_mixin(file.clone().clone(), remapping_vals.borrow_mut()));
 
@@ -475,6 +1194,75 @@ let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
 }
 }
 
+/// Commit SHA the server last recorded as fully indexed for a root, used by
+/// `update_git_aware` to restrict reconciliation to `git diff` output instead
+/// of a full walk. Stored as a plain string property rather than on the
+/// `Root` struct's own id so a root with no recorded commit (first run, or a
+/// non-Git root) just reads back empty instead of needing a sentinel.
+#[derive(Serialize, Deserialize)]
+pub struct getRootCommitShaInput {
+
+pub root_id: ID
+}
+#[handler(with_read)]
+pub fn getRootCommitSha (input: &HandlerInput) -> Result<Response, GraphError> {
+{
+    let root = G::new(Arc::clone(&db), &txn)
+.n_from_id(&data.root_id).collect_to_obj();
+    let commit_sha: String = G::new_from(Arc::clone(&db), &txn, vec![root.clone()])
+.check_property("commit_sha")
+.map_value_or(String::new(), |v| v.to_string())?;
+let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
+        return_vals.insert("commit_sha".to_string(), ReturnValue::from(Value::from(commit_sha)));
+
+}
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct setRootCommitShaInput {
+
+pub root_id: ID,
+pub commit_sha: String
+}
+#[handler(with_write)]
+pub fn setRootCommitSha (input: &HandlerInput) -> Result<Response, GraphError> {
+{
+    let update_tr = G::new(Arc::clone(&db), &txn)
+.n_from_id(&data.root_id)
+    .collect_to::<Vec<_>>();
+    let root = G::new_mut_from(Arc::clone(&db), &mut txn, update_tr)
+    .update(Some(props! { "commit_sha" => &data.commit_sha }))
+    .collect_to_obj();
+let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
+        return_vals.insert("root".to_string(), ReturnValue::from_traversal_value_with_mixin(root.clone().clone(), remapping_vals.borrow_mut()));
+
+}
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct pathBetweenEntitiesInput {
+
+pub from_entity_id: ID,
+pub to_entity_id: ID,
+pub edge_label: Option<String>,
+pub max_depth: i64
+}
+#[handler(with_read)]
+pub fn pathBetweenEntities (input: &HandlerInput) -> Result<Response, GraphError> {
+{
+    let edge_label = data.edge_label.clone().unwrap_or_else(|| "Entity_to_Entity".to_string());
+    let path = G::new(Arc::clone(&db), &txn)
+.n_from_id(&data.from_entity_id)
+
+.shortest_path(Some(&edge_label), Some(&data.to_entity_id), Some(data.max_depth as usize)).collect_to::<Vec<_>>();
+    let path_length: i64 = if path.is_empty() { 0 } else { path.len() as i64 - 1 };
+let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
+        return_vals.insert("path".to_string(), ReturnValue::from_traversal_value_array_with_mixin(path.clone(), remapping_vals.borrow_mut()));
+        return_vals.insert("length".to_string(), ReturnValue::from(Value::from(path_length)));
+
+}
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct getSubEntitiesInput {
 
@@ -501,30 +1289,146 @@ pub folder_id: ID
 #[handler(with_write)]
 pub fn deleteFolder (input: &HandlerInput) -> Result<Response, GraphError> {
 {
-    Drop::<Vec<_>>::drop_traversal(
-                G::new(Arc::clone(&db), &txn)
-.n_from_id(&data.folder_id)
+    // Walk the whole subtree before dropping anything: Folder_to_Folder and
+    // Folder_to_File downward, then File_to_Entity/Entity_to_Entity and
+    // Entity_to_EmbededCode from each contained file. `visited_*` doubles as
+    // the cycle guard for Entity_to_Entity.
+    let mut visited_folders: HashSet<ID> = HashSet::new();
+    let mut visited_files: HashSet<ID> = HashSet::new();
+    let mut visited_entities: HashSet<ID> = HashSet::new();
+    let mut visited_embeddings: HashSet<ID> = HashSet::new();
+
+    let mut folder_stack: Vec<ID> = vec![data.folder_id.clone()];
+    while let Some(folder_id) = folder_stack.pop() {
+        if !visited_folders.insert(folder_id.clone()) {
+            continue;
+        }
+        let subfolders = G::new(Arc::clone(&db), &txn)
+.n_from_id(&folder_id)
+
+.out("Folder_to_Folder",&EdgeType::Node).collect_to::<Vec<_>>();
+        for subfolder in &subfolders {
+            folder_stack.push(subfolder.id());
+        }
+
+        let files = G::new(Arc::clone(&db), &txn)
+.n_from_id(&folder_id)
+
+.out("Folder_to_File",&EdgeType::Node).collect_to::<Vec<_>>();
+        for file in &files {
+            if !visited_files.insert(file.id()) {
+                continue;
+            }
+            let mut entity_stack: Vec<ID> = G::new(Arc::clone(&db), &txn)
+.n_from_id(&file.id())
+
+.out("File_to_Entity",&EdgeType::Node).collect_to::<Vec<_>>().into_iter().map(|entity| entity.id()).collect();
+            while let Some(entity_id) = entity_stack.pop() {
+                if !visited_entities.insert(entity_id.clone()) {
+                    continue;
+                }
+                let sub_entities = G::new(Arc::clone(&db), &txn)
+.n_from_id(&entity_id)
+
+.out("Entity_to_Entity",&EdgeType::Node).collect_to::<Vec<_>>();
+                for sub_entity in &sub_entities {
+                    entity_stack.push(sub_entity.id());
+                }
+                let embeddings = G::new(Arc::clone(&db), &txn)
+.n_from_id(&entity_id)
+
+.out("Entity_to_EmbededCode",&EdgeType::Vec).collect_to::<Vec<_>>();
+                for embedding in &embeddings {
+                    visited_embeddings.insert(embedding.id());
+                }
+            }
+        }
+    }
+
+    let deleted_folders = visited_folders.len() as i64;
+    let deleted_files = visited_files.len() as i64;
+    let deleted_entities = visited_entities.len() as i64;
+    let deleted_embeddings = visited_embeddings.len() as i64;
+
+    // Drop bottom-up: embeddings and entities, then files, then folders.
+    for entity_id in &visited_entities {
+        Drop::<Vec<_>>::drop_traversal(
+                    G::new(Arc::clone(&db), &txn)
+.n_from_id(entity_id)
+
+.out("Entity_to_EmbededCode",&EdgeType::Vec).collect_to::<Vec<_>>(),
+                    Arc::clone(&db),
+                    &mut txn,
+                )?;
+        Drop::<Vec<_>>::drop_traversal(
+                    G::new(Arc::clone(&db), &txn)
+.n_from_id(entity_id)
+
+.in_e("File_to_Entity").collect_to::<Vec<_>>(),
+                    Arc::clone(&db),
+                    &mut txn,
+                )?;
+        Drop::<Vec<_>>::drop_traversal(
+                    G::new(Arc::clone(&db), &txn)
+.n_from_id(entity_id)
+
+.in_e("Entity_to_Entity").collect_to::<Vec<_>>(),
+                    Arc::clone(&db),
+                    &mut txn,
+                )?;
+        Drop::<Vec<_>>::drop_traversal(
+                    G::new(Arc::clone(&db), &txn)
+.n_from_id(entity_id).collect_to_obj(),
+                    Arc::clone(&db),
+                    &mut txn,
+                )?;
+    }
+    for file_id in &visited_files {
+        bm25_remove_file(file_id);
+        Drop::<Vec<_>>::drop_traversal(
+                    G::new(Arc::clone(&db), &txn)
+.n_from_id(file_id)
+
+.in_e("Folder_to_File").collect_to::<Vec<_>>(),
+                    Arc::clone(&db),
+                    &mut txn,
+                )?;
+        Drop::<Vec<_>>::drop_traversal(
+                    G::new(Arc::clone(&db), &txn)
+.n_from_id(file_id).collect_to_obj(),
+                    Arc::clone(&db),
+                    &mut txn,
+                )?;
+    }
+    for folder_id in &visited_folders {
+        Drop::<Vec<_>>::drop_traversal(
+                    G::new(Arc::clone(&db), &txn)
+.n_from_id(folder_id)
 
 .in_e("Folder_to_Folder").collect_to::<Vec<_>>(),
-                Arc::clone(&db),
-                &mut txn,
-            )?;;
-    Drop::<Vec<_>>::drop_traversal(
-                G::new(Arc::clone(&db), &txn)
-.n_from_id(&data.folder_id)
+                    Arc::clone(&db),
+                    &mut txn,
+                )?;
+        Drop::<Vec<_>>::drop_traversal(
+                    G::new(Arc::clone(&db), &txn)
+.n_from_id(folder_id)
 
 .in_e("Root_to_Folder").collect_to::<Vec<_>>(),
-                Arc::clone(&db),
-                &mut txn,
-            )?;;
-    Drop::<Vec<_>>::drop_traversal(
-                G::new(Arc::clone(&db), &txn)
-.n_from_id(&data.folder_id).collect_to_obj(),
-                Arc::clone(&db),
-                &mut txn,
-            )?;;
+                    Arc::clone(&db),
+                    &mut txn,
+                )?;
+        Drop::<Vec<_>>::drop_traversal(
+                    G::new(Arc::clone(&db), &txn)
+.n_from_id(folder_id).collect_to_obj(),
+                    Arc::clone(&db),
+                    &mut txn,
+                )?;
+    }
 let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
-        return_vals.insert("success".to_string(), ReturnValue::from(Value::from("success")));
+        return_vals.insert("deleted_folders".to_string(), ReturnValue::from(Value::from(deleted_folders)));
+        return_vals.insert("deleted_files".to_string(), ReturnValue::from(Value::from(deleted_files)));
+        return_vals.insert("deleted_entities".to_string(), ReturnValue::from(Value::from(deleted_entities)));
+        return_vals.insert("deleted_embeddings".to_string(), ReturnValue::from(Value::from(deleted_embeddings)));
 
 }
 }
@@ -581,8 +1485,15 @@ pub fn createSuperEntity (input: &HandlerInput) -> Result<Response, GraphError>
 {
     let file = G::new(Arc::clone(&db), &txn)
 .n_from_id(&data.file_id).collect_to_obj();
+    let source_revision: i64 = G::new_from(Arc::clone(&db), &txn, vec![file.clone()])
+.check_property("revision")
+.map_value_or(0i64, |v| v.to_string().parse::<i64>().unwrap_or(0))?;
+    let source_text_hash: String = G::new_from(Arc::clone(&db), &txn, vec![file.clone()])
+.check_property("text_hash")
+.map_value_or(String::new(), |v| v.to_string())?;
+    let source_hash = BLOB_STORE.get(&source_text_hash).map(|bytes| content_hash(&String::from_utf8_lossy(&bytes))).unwrap_or_default();
     let entity = G::new_mut(Arc::clone(&db), &mut txn)
-.add_n("Entity", Some(props! { "entity_type" => &data.entity_type, "order" => &data.order, "extracted_at" => chrono::Utc::now().to_rfc3339(), "start_byte" => &data.start_byte, "text" => &data.text, "end_byte" => &data.end_byte }), None).collect_to_obj();
+.add_n("Entity", Some(props! { "entity_type" => &data.entity_type, "order" => &data.order, "extracted_at" => chrono::Utc::now().to_rfc3339(), "start_byte" => &data.start_byte, "text" => &data.text, "end_byte" => &data.end_byte, "source_revision" => &source_revision, "content_hash" => &source_hash }), None).collect_to_obj();
     G::new_mut(Arc::clone(&db), &mut txn)
 .add_e("File_to_Entity", None, file.id(), entity.id(), true, EdgeType::Node).collect_to_obj();
 let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
@@ -591,6 +1502,63 @@ let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
 }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct ingestFileInput {
+
+pub file_id: ID,
+pub language: String,
+pub text: String
+}
+#[handler(with_write)]
+pub fn ingestFile (input: &HandlerInput) -> Result<Response, GraphError> {
+{
+    let file = G::new(Arc::clone(&db), &txn)
+.n_from_id(&data.file_id).collect_to_obj();
+    let source_revision: i64 = G::new_from(Arc::clone(&db), &txn, vec![file.clone()])
+.check_property("revision")
+.map_value_or(0i64, |v| v.to_string().parse::<i64>().unwrap_or(0))?;
+    let source_hash = content_hash(&data.text);
+    let mut entity_count: i64 = 0;
+    if let Some(language) = get_ts_language(&data.language) {
+        let mut parser = tree_sitter::Parser::new();
+        if parser.set_language(&language).is_ok() {
+            if let Some(tree) = parser.parse(&data.text, None) {
+                let mut order_counters: HashMap<ID, i64> = HashMap::new();
+                let mut stack: Vec<(tree_sitter::Node, ID)> = vec![(tree.root_node(), file.id())];
+                while let Some((node, parent_id)) = stack.pop() {
+                    let mut cursor = node.walk();
+                    for child in node.children(&mut cursor).collect::<Vec<_>>() {
+                        if is_entity_kind(child.kind()) {
+                            let order = {
+                                let counter = order_counters.entry(parent_id.clone()).or_insert(0);
+                                *counter += 1;
+                                *counter
+                            };
+                            let start_byte = child.start_byte() as i64;
+                            let end_byte = child.end_byte() as i64;
+                            let text = data.text.get(child.start_byte()..child.end_byte()).unwrap_or("").to_string();
+                            let entity = G::new_mut(Arc::clone(&db), &mut txn)
+.add_n("Entity", Some(props! { "entity_type" => child.kind(), "start_byte" => &start_byte, "end_byte" => &end_byte, "order" => &order, "text" => &text, "extracted_at" => chrono::Utc::now().to_rfc3339(), "source_revision" => &source_revision, "content_hash" => &source_hash }), None).collect_to_obj();
+                            let edge_type = if parent_id == file.id() { "File_to_Entity" } else { "Entity_to_Entity" };
+                            G::new_mut(Arc::clone(&db), &mut txn)
+.add_e(edge_type, None, parent_id.clone(), entity.id(), true, EdgeType::Node).collect_to_obj();
+                            entity_count += 1;
+                            stack.push((child, entity.id()));
+                        } else {
+                            stack.push((child, parent_id.clone()));
+                        }
+                    }
+                }
+            }
+        }
+    }
+let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
+        return_vals.insert("file".to_string(), ReturnValue::from_traversal_value_with_mixin(file.clone().clone(), remapping_vals.borrow_mut()));
+        return_vals.insert("entity_count".to_string(), ReturnValue::from(Value::from(entity_count)));
+
+}
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct getFileEntitiesInput {
 
@@ -629,6 +1597,113 @@ let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
 }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct searchSimilarEntitiesInput {
+
+pub vector: Vec<f64>,
+pub k: i64,
+pub root_id: Option<ID>
+}
+/// Top-k cosine-similarity search over `Entity_to_EmbededCode` vectors.
+/// `search_v` already gives us an approximate-nearest-neighbor candidate
+/// set without a full linear scan (the same incrementally-maintained index
+/// `embedSuperEntity`'s `insert_v` feeds on every write), so this handler
+/// leans on it for candidate generation instead of duplicating that index,
+/// then re-scores the candidates by exact cosine similarity and attaches
+/// each as a `score` field via the same per-item mixin mechanism
+/// `exclude_field!` uses elsewhere in this file. `root_id`, if given,
+/// restricts candidates to entities reachable from that root via
+/// `Root_to_File`/`File_to_Entity`.
+#[handler(with_read)]
+pub fn searchSimilarEntities (input: &HandlerInput) -> Result<Response, GraphError> {
+{
+    let entity_scope: Option<HashSet<ID>> = data.root_id.as_ref().map(|root_id| {
+        G::new(Arc::clone(&db), &txn)
+.n_from_id(root_id)
+
+.out("Root_to_File",&EdgeType::Node)
+
+.out("File_to_Entity",&EdgeType::Node).collect_to::<Vec<_>>().into_iter().map(|entity| entity.id()).collect()
+    });
+
+    let vector_hits = G::new(Arc::clone(&db), &txn)
+.search_v::<fn(&HVector, &RoTxn) -> bool>(&data.vector, data.k as usize, None).collect_to::<Vec<_>>();
+
+    let mut scores: HashMap<ID, f64> = HashMap::new();
+    let mut ranked: Vec<TraversalVal> = Vec::new();
+    for vector_hit in &vector_hits {
+        let vector_text: String = G::new_from(Arc::clone(&db), &txn, vec![vector_hit.clone()])
+.check_property("vector")
+.map_value_or(String::new(), |v| v.to_string())?;
+        let candidate_vector = parse_vector_property(&vector_text);
+        let score = cosine_similarity(&data.vector, &candidate_vector);
+        let entities = G::new_from(Arc::clone(&db), &txn, vec![vector_hit.clone()])
+
+.in_("Entity_to_EmbededCode",&EdgeType::Node).collect_to::<Vec<_>>();
+        for entity in entities {
+            if entity_scope.as_ref().map_or(true, |scope| scope.contains(&entity.id())) {
+                scores.insert(entity.id(), score);
+                ranked.push(entity);
+            }
+        }
+    }
+    ranked.sort_by(|a, b| scores[&b.id()].partial_cmp(&scores[&a.id()]).unwrap());
+    ranked.truncate(data.k.max(0) as usize);
+
+let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
+        return_vals.insert("entities".to_string(), ReturnValue::from_traversal_value_array_with_mixin(G::new_from(Arc::clone(&db), &txn, ranked.clone())
+
+.map_traversal(|item, txn| { exclude_field!(remapping_vals, item.clone(), "text")?;
+ value_remapping!(remapping_vals, item.clone(), "score", *scores.get(&item.id()).unwrap_or(&0.0))?;
+ Ok(item) }).collect_to::<Vec<_>>().clone(), remapping_vals.borrow_mut()));
+
+}
+}
+
+/// Pure-vector top-k search over `Entity_to_EmbededCode` embeddings, used by
+/// `search_code`'s semantic pass. Unlike `searchSimilarEntities` this has no
+/// optional root scoping and doesn't exclude `text` — the caller reranks
+/// candidates against their own text locally and needs it in the response.
+#[derive(Serialize, Deserialize)]
+pub struct vectorSearchInput {
+
+pub vector: Vec<f64>,
+pub k: i64
+}
+#[handler(with_read)]
+pub fn vectorSearch (input: &HandlerInput) -> Result<Response, GraphError> {
+{
+    let vector_hits = G::new(Arc::clone(&db), &txn)
+.search_v::<fn(&HVector, &RoTxn) -> bool>(&data.vector, data.k as usize, None).collect_to::<Vec<_>>();
+
+    let mut scores: HashMap<ID, f64> = HashMap::new();
+    let mut ranked: Vec<TraversalVal> = Vec::new();
+    for vector_hit in &vector_hits {
+        let vector_text: String = G::new_from(Arc::clone(&db), &txn, vec![vector_hit.clone()])
+.check_property("vector")
+.map_value_or(String::new(), |v| v.to_string())?;
+        let candidate_vector = parse_vector_property(&vector_text);
+        let score = cosine_similarity(&data.vector, &candidate_vector);
+        let entities = G::new_from(Arc::clone(&db), &txn, vec![vector_hit.clone()])
+
+.in_("Entity_to_EmbededCode",&EdgeType::Node).collect_to::<Vec<_>>();
+        for entity in entities {
+            scores.insert(entity.id(), score);
+            ranked.push(entity);
+        }
+    }
+    ranked.sort_by(|a, b| scores[&b.id()].partial_cmp(&scores[&a.id()]).unwrap());
+    ranked.truncate(data.k.max(0) as usize);
+
+let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
+        return_vals.insert("entity".to_string(), ReturnValue::from_traversal_value_array_with_mixin(G::new_from(Arc::clone(&db), &txn, ranked.clone())
+
+.map_traversal(|item, txn| { value_remapping!(remapping_vals, item.clone(), "score", *scores.get(&item.id()).unwrap_or(&0.0))?;
+ Ok(item) }).collect_to::<Vec<_>>().clone(), remapping_vals.borrow_mut()));
+
+}
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct getAllFoldersInput {
 
@@ -676,6 +1751,102 @@ let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
 }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct queryEntitiesInput {
+
+pub filter: String
+}
+#[handler(with_read)]
+pub fn queryEntities (input: &HandlerInput) -> Result<Response, GraphError> {
+{
+    let expr = parse_filter_expr(&data.filter);
+    let entity = match expr {
+        Some(expr) => G::new(Arc::clone(&db), &txn)
+.n_from_type("Entity")
+
+.filter_ref(|val, txn|{
+                if let Ok(val) = val {
+                    let val_clone = val.clone();
+                    let result = eval_filter_expr(&expr, &mut |name: &str| {
+                        let resolved = G::new_from(Arc::clone(&db), &txn, val_clone.clone())
+
+.check_property(name)
+
+.map_value_or(None, |v| Some(filter_value_from_property(v)));
+                        resolved.ok_or_else(|| GraphError::New(format!(
+                            "queryEntities: unknown property `{}`", name
+                        )))
+                    })?;
+                    Ok(matches!(result, FilterValue::Bool(true)))
+                } else {
+                    Ok(false)
+                }
+            }).collect_to::<Vec<_>>(),
+        None => Vec::new(),
+    };
+let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
+        return_vals.insert("entities".to_string(), ReturnValue::from_traversal_value_array_with_mixin(entity.clone(), remapping_vals.borrow_mut()));
+
+}
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct getStaleEntitiesInput {
+
+pub file_id: Option<ID>,
+pub root_id: Option<ID>
+}
+#[handler(with_read)]
+pub fn getStaleEntities (input: &HandlerInput) -> Result<Response, GraphError> {
+{
+    let files: Vec<TraversalVal> = if let Some(file_id) = &data.file_id {
+        G::new(Arc::clone(&db), &txn)
+.n_from_id(file_id).collect_to::<Vec<_>>()
+    } else if let Some(root_id) = &data.root_id {
+        G::new(Arc::clone(&db), &txn)
+.n_from_id(root_id)
+
+.out("Root_to_File",&EdgeType::Node).collect_to::<Vec<_>>()
+    } else {
+        Vec::new()
+    };
+
+    let mut stale: Vec<TraversalVal> = Vec::new();
+    for file in &files {
+        let current_revision: i64 = G::new_from(Arc::clone(&db), &txn, vec![file.clone()])
+.check_property("revision")
+.map_value_or(0i64, |v| v.to_string().parse::<i64>().unwrap_or(0))?;
+        let current_text_hash: String = G::new_from(Arc::clone(&db), &txn, vec![file.clone()])
+.check_property("text_hash")
+.map_value_or(String::new(), |v| v.to_string())?;
+        let current_hash = BLOB_STORE.get(&current_text_hash).map(|bytes| content_hash(&String::from_utf8_lossy(&bytes))).unwrap_or_default();
+
+        let entities = G::new(Arc::clone(&db), &txn)
+.n_from_id(&file.id())
+
+.out("File_to_Entity",&EdgeType::Node).collect_to::<Vec<_>>();
+
+        for entity in entities {
+            let entity_revision: i64 = G::new_from(Arc::clone(&db), &txn, vec![entity.clone()])
+.check_property("source_revision")
+.map_value_or(-1i64, |v| v.to_string().parse::<i64>().unwrap_or(-1))?;
+            let entity_hash: String = G::new_from(Arc::clone(&db), &txn, vec![entity.clone()])
+.check_property("content_hash")
+.map_value_or(String::new(), |v| v.to_string())?;
+            if entity_revision < current_revision || entity_hash != current_hash {
+                stale.push(entity);
+            }
+        }
+    }
+let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
+        return_vals.insert("entities".to_string(), ReturnValue::from_traversal_value_array_with_mixin(G::new_from(Arc::clone(&db), &txn, stale.clone())
+
+.map_traversal(|item, txn| { exclude_field!(remapping_vals, item.clone(), "text")?;
+ Ok(item) }).collect_to::<Vec<_>>().clone(), remapping_vals.borrow_mut()));
+
+}
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct deleteFileInput {
 
@@ -684,6 +1855,7 @@ pub file_id: ID
 #[handler(with_write)]
 pub fn deleteFile (input: &HandlerInput) -> Result<Response, GraphError> {
 {
+    bm25_remove_file(&data.file_id);
     Drop::<Vec<_>>::drop_traversal(
                 G::new(Arc::clone(&db), &txn)
 .n_from_id(&data.file_id)
@@ -786,9 +1958,45 @@ pub fn updateFile (input: &HandlerInput) -> Result<Response, GraphError> {
 {
     let file = {let update_tr = G::new(Arc::clone(&db), &txn)
 .n_from_id(&data.file_id)
-    .collect_to::<Vec<_>>();G::new_mut_from(Arc::clone(&db), &mut txn, update_tr)
-    .update(Some(props! { "text" => &data.text, "extracted_at" => &data.extracted_at }))
+    .collect_to::<Vec<_>>();
+    let current_revision: i64 = G::new_from(Arc::clone(&db), &txn, update_tr.clone())
+.check_property("revision")
+.map_value_or(0i64, |v| v.to_string().parse::<i64>().unwrap_or(0))?;
+    let next_revision = current_revision + 1;
+    let text_hash = BLOB_STORE.put(data.text.as_bytes());
+    let text_backend = BLOB_STORE.backend_id().to_string();
+    G::new_mut_from(Arc::clone(&db), &mut txn, update_tr)
+    .update(Some(props! { "text_hash" => &text_hash, "text_backend" => &text_backend, "extracted_at" => &data.extracted_at, "revision" => &next_revision }))
     .collect_to_obj()};
+    bm25_index_file(file.id(), &data.text);
+let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
+        return_vals.insert("file".to_string(), ReturnValue::from_traversal_value_with_mixin(file.clone().clone(), remapping_vals.borrow_mut()));
+
+}
+}
+
+/// Renames a file in place after `detect_renames` matches it to a moved path
+/// by content hash, so a rename/move keeps the file's id and entities instead
+/// of going through delete-then-recreate. `hash` is the content hash
+/// `detect_renames` already matched on; it's re-stored here (rather than
+/// trusted as unchanged) so this handler stays correct if a future caller
+/// ever renames a file whose content also changed.
+#[derive(Serialize, Deserialize)]
+pub struct renameFileInput {
+
+pub file_id: ID,
+pub name: String,
+pub hash: String
+}
+#[handler(with_write)]
+pub fn renameFile (input: &HandlerInput) -> Result<Response, GraphError> {
+{
+    let update_tr = G::new(Arc::clone(&db), &txn)
+.n_from_id(&data.file_id)
+    .collect_to::<Vec<_>>();
+    let file = G::new_mut_from(Arc::clone(&db), &mut txn, update_tr)
+    .update(Some(props! { "name" => &data.name, "text_hash" => &data.hash }))
+    .collect_to_obj();
 let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
         return_vals.insert("file".to_string(), ReturnValue::from_traversal_value_with_mixin(file.clone().clone(), remapping_vals.borrow_mut()));
 
@@ -860,10 +2068,7 @@ pub fn getFile (input: &HandlerInput) -> Result<Response, GraphError> {
     let file = G::new(Arc::clone(&db), &txn)
 .n_from_id(&data.file_id).collect_to_obj();
 let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
-        return_vals.insert("file".to_string(), ReturnValue::from_traversal_value_array_with_mixin(G::new_from(Arc::clone(&db), &txn, file.clone())
-
-.map_traversal(|item, txn| { exclude_field!(remapping_vals, item.clone(), "text")?;
- Ok(item) }).collect_to::<Vec<_>>().clone(), remapping_vals.borrow_mut()));
+        return_vals.insert("file".to_string(), ReturnValue::from_traversal_value_array_with_mixin(file.clone().clone(), remapping_vals.borrow_mut()));
 
 }
 }
@@ -881,10 +2086,7 @@ pub fn getRootFiles (input: &HandlerInput) -> Result<Response, GraphError> {
 
 .out("Root_to_File",&EdgeType::Node).collect_to::<Vec<_>>();
 let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
-        return_vals.insert("files".to_string(), ReturnValue::from_traversal_value_array_with_mixin(G::new_from(Arc::clone(&db), &txn, files.clone())
-
-.map_traversal(|item, txn| { exclude_field!(remapping_vals, item.clone(), "text")?;
- Ok(item) }).collect_to::<Vec<_>>().clone(), remapping_vals.borrow_mut()));
+        return_vals.insert("files".to_string(), ReturnValue::from_traversal_value_array_with_mixin(files.clone().clone(), remapping_vals.borrow_mut()));
 
 }
 }
@@ -912,10 +2114,87 @@ pub fn getFileByExtension (input: &HandlerInput) -> Result<Response, GraphError>
                 }
             }).collect_to::<Vec<_>>();
 let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
-        return_vals.insert("files".to_string(), ReturnValue::from_traversal_value_array_with_mixin(G::new_from(Arc::clone(&db), &txn, files.clone())
+        return_vals.insert("files".to_string(), ReturnValue::from_traversal_value_array_with_mixin(files.clone().clone(), remapping_vals.borrow_mut()));
 
-.map_traversal(|item, txn| { exclude_field!(remapping_vals, item.clone(), "text")?;
- Ok(item) }).collect_to::<Vec<_>>().clone(), remapping_vals.borrow_mut()));
+}
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct searchFilesInput {
+
+pub query: String,
+pub root_id: Option<ID>,
+pub limit: i64
+}
+#[handler(with_read)]
+pub fn searchFiles (input: &HandlerInput) -> Result<Response, GraphError> {
+{
+    // BM25_INDEX only tracks what this process has indexed since it
+    // started; on the first call after a restart, rebuild it from the
+    // File nodes already in the graph so a warm re-run of searchFiles
+    // doesn't silently see nothing for files ingested in a prior
+    // process lifetime.
+    if !BM25_BUILT.swap(true, std::sync::atomic::Ordering::SeqCst) {
+        let files = G::new(Arc::clone(&db), &txn)
+.n_from_type("File").collect_to::<Vec<_>>();
+        for file in &files {
+            let text_hash: String = G::new_from(Arc::clone(&db), &txn, vec![file.clone()])
+.check_property("text_hash")
+.map_value_or(String::new(), |v| v.to_string())?;
+            if text_hash.is_empty() {
+                continue;
+            }
+            let text = BLOB_STORE.get(&text_hash).map(|bytes| String::from_utf8_lossy(&bytes).into_owned()).unwrap_or_default();
+            bm25_index_file(file.id(), &text);
+        }
+    }
+
+    let query_tokens = tokenize_bm25(&data.query);
+
+    let mut scores: HashMap<ID, f64> = HashMap::new();
+    {
+        let index = BM25_INDEX.lock().unwrap();
+        let lengths = BM25_DOC_LENGTHS.lock().unwrap();
+        let n = lengths.len() as f64;
+        let avgdl = if lengths.is_empty() { 1.0 } else { lengths.values().sum::<usize>() as f64 / lengths.len() as f64 };
+        const K1: f64 = 1.2;
+        const B: f64 = 0.75;
+
+        for token in &query_tokens {
+            if let Some(postings) = index.get(token) {
+                let df = postings.len() as f64;
+                let idf = ((n - df + 0.5) / (df + 0.5) + 1.0).ln();
+                for (file_id, &tf) in postings {
+                    let dl = *lengths.get(file_id).unwrap_or(&0) as f64;
+                    let denom = tf as f64 + K1 * (1.0 - B + B * dl / avgdl.max(1.0));
+                    let contribution = idf * (tf as f64 * (K1 + 1.0)) / denom;
+                    *scores.entry(file_id.clone()).or_insert(0.0) += contribution;
+                }
+            }
+        }
+    }
+
+    let root_scope: Option<HashSet<ID>> = data.root_id.as_ref().map(|root_id| {
+        G::new(Arc::clone(&db), &txn)
+.n_from_id(root_id)
+
+.out("Root_to_File",&EdgeType::Node).collect_to::<Vec<_>>().into_iter().map(|file| file.id()).collect()
+    });
+
+    let mut ranked: Vec<(ID, f64)> = scores
+        .into_iter()
+        .filter(|(file_id, _)| root_scope.as_ref().map_or(true, |scope| scope.contains(file_id)))
+        .collect();
+    ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+    ranked.truncate(data.limit.max(0) as usize);
+
+    let files: Vec<_> = ranked.into_iter()
+        .filter_map(|(file_id, _)| G::new(Arc::clone(&db), &txn)
+.n_from_id(&file_id).collect_to::<Vec<_>>().into_iter().next())
+        .collect();
+
+let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
+        return_vals.insert("files".to_string(), ReturnValue::from_traversal_value_array_with_mixin(files.clone().clone(), remapping_vals.borrow_mut()));
 
 }
 }
@@ -956,3 +2235,244 @@ let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
 
 }
 }
+
+#[derive(Serialize, Deserialize)]
+pub struct hybridSearchEntityInput {
+
+pub query: String,
+pub vector: Vec<f64>,
+pub k: i64
+}
+#[handler(with_read)]
+pub fn hybridSearchEntity (input: &HandlerInput) -> Result<Response, GraphError> {
+{
+    let bm25_entities = G::new(Arc::clone(&db), &txn)
+.search_bm25::<fn(&RoTxn) -> bool>(&data.query, data.k as usize, None).collect_to::<Vec<_>>();
+    let vector_hits = G::new(Arc::clone(&db), &txn)
+.search_v::<fn(&HVector, &RoTxn) -> bool>(&data.vector, data.k as usize, None).collect_to::<Vec<_>>();
+    let vector_entities = G::new_from(Arc::clone(&db), &txn, vector_hits.clone())
+
+.in_("Entity_to_EmbededCode",&EdgeType::Node).collect_to::<Vec<_>>();
+    const RRF_C: f64 = 60.0;
+    let mut fused_scores: HashMap<ID, f64> = HashMap::new();
+    let mut fused_entities: HashMap<ID, TraversalVal> = HashMap::new();
+    for (rank, entity) in bm25_entities.iter().enumerate() {
+        *fused_scores.entry(entity.id()).or_insert(0.0) += 1.0 / (RRF_C + (rank + 1) as f64);
+        fused_entities.entry(entity.id()).or_insert_with(|| entity.clone());
+    }
+    for (rank, entity) in vector_entities.iter().enumerate() {
+        *fused_scores.entry(entity.id()).or_insert(0.0) += 1.0 / (RRF_C + (rank + 1) as f64);
+        fused_entities.entry(entity.id()).or_insert_with(|| entity.clone());
+    }
+    let mut ranked_ids: Vec<ID> = fused_scores.keys().cloned().collect();
+    ranked_ids.sort_by(|a, b| fused_scores[b].partial_cmp(&fused_scores[a]).unwrap());
+    ranked_ids.truncate(data.k as usize);
+    let entity: Vec<_> = ranked_ids.into_iter().filter_map(|id| fused_entities.get(&id).cloned()).collect();
+let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
+        return_vals.insert("entity".to_string(), ReturnValue::from_traversal_value_array_with_mixin(entity.clone(), remapping_vals.borrow_mut()));
+
+}
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct moveFolderInput {
+
+pub folder_id: ID,
+pub new_parent_folder_id: ID
+}
+#[handler(with_write)]
+pub fn moveFolder (input: &HandlerInput) -> Result<Response, GraphError> {
+{
+    // Cycle guard: the new parent can't be the folder itself or any of its
+    // own descendants (walked via Folder_to_Folder), or re-pointing the
+    // incoming edge below would detach the subtree from the graph.
+    let mut descendants: HashSet<ID> = HashSet::new();
+    let mut folder_stack: Vec<ID> = vec![data.folder_id.clone()];
+    let mut is_cycle = false;
+    while let Some(folder_id) = folder_stack.pop() {
+        if !descendants.insert(folder_id.clone()) {
+            continue;
+        }
+        if folder_id == data.new_parent_folder_id {
+            is_cycle = true;
+        }
+        let subfolders = G::new(Arc::clone(&db), &txn)
+.n_from_id(&folder_id)
+
+.out("Folder_to_Folder",&EdgeType::Node).collect_to::<Vec<_>>();
+        for subfolder in &subfolders {
+            folder_stack.push(subfolder.id());
+        }
+    }
+
+    if !is_cycle {
+        Drop::<Vec<_>>::drop_traversal(
+                    G::new(Arc::clone(&db), &txn)
+.n_from_id(&data.folder_id)
+
+.in_e("Folder_to_Folder").collect_to::<Vec<_>>(),
+                    Arc::clone(&db),
+                    &mut txn,
+                )?;
+        Drop::<Vec<_>>::drop_traversal(
+                    G::new(Arc::clone(&db), &txn)
+.n_from_id(&data.folder_id)
+
+.in_e("Root_to_Folder").collect_to::<Vec<_>>(),
+                    Arc::clone(&db),
+                    &mut txn,
+                )?;
+        G::new_mut(Arc::clone(&db), &mut txn)
+.add_e("Folder_to_Folder", None, data.new_parent_folder_id.clone(), data.folder_id.clone(), true, EdgeType::Node).collect_to_obj();
+    }
+
+    let folder = G::new(Arc::clone(&db), &txn)
+.n_from_id(&data.folder_id).collect_to_obj();
+let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
+        return_vals.insert("folder".to_string(), ReturnValue::from_traversal_value_with_mixin(folder.clone().clone(), remapping_vals.borrow_mut()));
+        return_vals.insert("moved".to_string(), ReturnValue::from(Value::from(!is_cycle)));
+
+}
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct copyFolderInput {
+
+pub folder_id: ID,
+pub new_parent_folder_id: ID
+}
+#[handler(with_write)]
+pub fn copyFolder (input: &HandlerInput) -> Result<Response, GraphError> {
+{
+    // Deep-clone the subtree depth-first: each stack entry is the old
+    // folder being cloned paired with the already-cloned new parent it
+    // should attach under, so children always re-parent onto the copy
+    // rather than the original.
+    let mut folder_stack: Vec<(ID, ID)> = vec![(data.folder_id.clone(), data.new_parent_folder_id.clone())];
+    let mut new_root_folder: Option<TraversalVal> = None;
+    let mut copied_folders: i64 = 0;
+    let mut copied_files: i64 = 0;
+    let mut copied_entities: i64 = 0;
+    let mut copied_embeddings: i64 = 0;
+
+    while let Some((old_folder_id, new_parent_id)) = folder_stack.pop() {
+        let old_folder = G::new(Arc::clone(&db), &txn)
+.n_from_id(&old_folder_id).collect_to_obj();
+        let name: String = G::new_from(Arc::clone(&db), &txn, vec![old_folder.clone()])
+.check_property("name")
+.map_value_or(String::new(), |v| v.to_string())?;
+        let new_folder = G::new_mut(Arc::clone(&db), &mut txn)
+.add_n("Folder", Some(props! { "name" => &name, "extracted_at" => chrono::Utc::now().to_rfc3339() }), None).collect_to_obj();
+        G::new_mut(Arc::clone(&db), &mut txn)
+.add_e("Folder_to_Folder", None, new_parent_id.clone(), new_folder.id(), true, EdgeType::Node).collect_to_obj();
+        copied_folders += 1;
+        if old_folder_id == data.folder_id {
+            new_root_folder = Some(new_folder.clone());
+        }
+
+        let subfolders = G::new(Arc::clone(&db), &txn)
+.n_from_id(&old_folder_id)
+
+.out("Folder_to_Folder",&EdgeType::Node).collect_to::<Vec<_>>();
+        for subfolder in &subfolders {
+            folder_stack.push((subfolder.id(), new_folder.id()));
+        }
+
+        let files = G::new(Arc::clone(&db), &txn)
+.n_from_id(&old_folder_id)
+
+.out("Folder_to_File",&EdgeType::Node).collect_to::<Vec<_>>();
+        for file in &files {
+            let file_name: String = G::new_from(Arc::clone(&db), &txn, vec![file.clone()])
+.check_property("name")
+.map_value_or(String::new(), |v| v.to_string())?;
+            let extension: String = G::new_from(Arc::clone(&db), &txn, vec![file.clone()])
+.check_property("extension")
+.map_value_or(String::new(), |v| v.to_string())?;
+            let text_hash: String = G::new_from(Arc::clone(&db), &txn, vec![file.clone()])
+.check_property("text_hash")
+.map_value_or(String::new(), |v| v.to_string())?;
+            let text_backend: String = G::new_from(Arc::clone(&db), &txn, vec![file.clone()])
+.check_property("text_backend")
+.map_value_or(String::new(), |v| v.to_string())?;
+            let new_file = G::new_mut(Arc::clone(&db), &mut txn)
+.add_n("File", Some(props! { "name" => &file_name, "extension" => &extension, "text_hash" => &text_hash, "text_backend" => &text_backend, "extracted_at" => chrono::Utc::now().to_rfc3339(), "revision" => &1i64 }), None).collect_to_obj();
+            G::new_mut(Arc::clone(&db), &mut txn)
+.add_e("Folder_to_File", None, new_folder.id(), new_file.id(), true, EdgeType::Node).collect_to_obj();
+            copied_files += 1;
+
+            let blob_bytes = BLOB_STORE.get(&text_hash).unwrap_or_default();
+            let blob_text = String::from_utf8_lossy(&blob_bytes).into_owned();
+            bm25_index_file(new_file.id(), &blob_text);
+
+            let mut entity_stack: Vec<(ID, ID)> = G::new(Arc::clone(&db), &txn)
+.n_from_id(&file.id())
+
+.out("File_to_Entity",&EdgeType::Node).collect_to::<Vec<_>>().into_iter().map(|entity| (entity.id(), new_file.id())).collect();
+            while let Some((old_entity_id, new_parent_id)) = entity_stack.pop() {
+                let old_entity = G::new(Arc::clone(&db), &txn)
+.n_from_id(&old_entity_id).collect_to_obj();
+                let entity_type: String = G::new_from(Arc::clone(&db), &txn, vec![old_entity.clone()])
+.check_property("entity_type")
+.map_value_or(String::new(), |v| v.to_string())?;
+                let start_byte: i64 = G::new_from(Arc::clone(&db), &txn, vec![old_entity.clone()])
+.check_property("start_byte")
+.map_value_or(0i64, |v| v.to_string().parse::<i64>().unwrap_or(0))?;
+                let end_byte: i64 = G::new_from(Arc::clone(&db), &txn, vec![old_entity.clone()])
+.check_property("end_byte")
+.map_value_or(0i64, |v| v.to_string().parse::<i64>().unwrap_or(0))?;
+                let order: i64 = G::new_from(Arc::clone(&db), &txn, vec![old_entity.clone()])
+.check_property("order")
+.map_value_or(0i64, |v| v.to_string().parse::<i64>().unwrap_or(0))?;
+                let text: String = G::new_from(Arc::clone(&db), &txn, vec![old_entity.clone()])
+.check_property("text")
+.map_value_or(String::new(), |v| v.to_string())?;
+                let source_revision: i64 = G::new_from(Arc::clone(&db), &txn, vec![old_entity.clone()])
+.check_property("source_revision")
+.map_value_or(0i64, |v| v.to_string().parse::<i64>().unwrap_or(0))?;
+                let content_hash_prop: String = G::new_from(Arc::clone(&db), &txn, vec![old_entity.clone()])
+.check_property("content_hash")
+.map_value_or(String::new(), |v| v.to_string())?;
+                let new_entity = G::new_mut(Arc::clone(&db), &mut txn)
+.add_n("Entity", Some(props! { "entity_type" => &entity_type, "start_byte" => &start_byte, "end_byte" => &end_byte, "order" => &order, "text" => &text, "extracted_at" => chrono::Utc::now().to_rfc3339(), "source_revision" => &source_revision, "content_hash" => &content_hash_prop }), None).collect_to_obj();
+                let edge_type = if new_parent_id == new_file.id() { "File_to_Entity" } else { "Entity_to_Entity" };
+                G::new_mut(Arc::clone(&db), &mut txn)
+.add_e(edge_type, None, new_parent_id.clone(), new_entity.id(), true, EdgeType::Node).collect_to_obj();
+                copied_entities += 1;
+
+                let sub_entities = G::new(Arc::clone(&db), &txn)
+.n_from_id(&old_entity_id)
+
+.out("Entity_to_Entity",&EdgeType::Node).collect_to::<Vec<_>>();
+                for sub_entity in &sub_entities {
+                    entity_stack.push((sub_entity.id(), new_entity.id()));
+                }
+
+                let embeddings = G::new(Arc::clone(&db), &txn)
+.n_from_id(&old_entity_id)
+
+.out("Entity_to_EmbededCode",&EdgeType::Vec).collect_to::<Vec<_>>();
+                for embedding in &embeddings {
+                    let vector_text: String = G::new_from(Arc::clone(&db), &txn, vec![embedding.clone()])
+.check_property("vector")
+.map_value_or(String::new(), |v| v.to_string())?;
+                    let vector = parse_vector_property(&vector_text);
+                    let new_embedding = G::new_mut(Arc::clone(&db), &mut txn)
+.insert_v::<fn(&HVector, &RoTxn) -> bool>(&vector, "EmbededCode", None).collect_to_obj();
+                    G::new_mut(Arc::clone(&db), &mut txn)
+.add_e("Entity_to_EmbededCode", None, new_entity.id(), new_embedding.id(), true, EdgeType::Node).collect_to_obj();
+                    copied_embeddings += 1;
+                }
+            }
+        }
+    }
+
+let mut return_vals: HashMap<String, ReturnValue> = HashMap::new();
+        return_vals.insert("folder".to_string(), ReturnValue::from_traversal_value_with_mixin(new_root_folder.unwrap().clone(), remapping_vals.borrow_mut()));
+        return_vals.insert("copied_folders".to_string(), ReturnValue::from(Value::from(copied_folders)));
+        return_vals.insert("copied_files".to_string(), ReturnValue::from(Value::from(copied_files)));
+        return_vals.insert("copied_entities".to_string(), ReturnValue::from(Value::from(copied_entities)));
+        return_vals.insert("copied_embeddings".to_string(), ReturnValue::from(Value::from(copied_embeddings)));
+
+}
+}